@@ -0,0 +1,117 @@
+//! Signing with a remote key management service (KMS) or HSM.
+//!
+//! The signing key never needs to be loaded in memory: the signer only has to
+//! implement the `Signer` and `MessageSigner` traits, whose methods are
+//! asynchronous and can call out to a remote signing backend.
+use std::{borrow::Cow, future::Future, pin::Pin, sync::Arc};
+
+use ssi::{
+    claims::{data_integrity::ProofOptions, jws, SignatureError},
+    crypto::algorithm::ES256OrES384,
+    dids::{AnyDidMethod, DIDKey, DIDResolver},
+    jwk::Algorithm,
+    verification_methods::{MessageSignatureError, MessageSigner, Multikey, Signer},
+    JWK,
+};
+use static_iref::uri;
+use w3c_vc_barcodes::{
+    optical_barcode_credential::{encode_to_bytes, SignatureParameters},
+    MachineReadableZone, MRZ,
+};
+
+/// Machine Readable Zone on the card.
+const MRZ_DATA: MRZ = [
+    *b"IAUTO0000007010SRC0000000701<<",
+    *b"8804192M2601058NOT<<<<<<<<<<<5",
+    *b"SMITH<<JOHN<<<<<<<<<<<<<<<<<<<",
+];
+
+type BoxFuture<'a, T> = Pin<Box<dyn 'a + Send + Future<Output = T>>>;
+
+/// Object-safe signing backend, such as a KMS or HSM client.
+trait KmsBackend: Send + Sync {
+    fn sign<'a>(
+        &'a self,
+        algorithm: ES256OrES384,
+        message: &'a [u8],
+    ) -> BoxFuture<'a, Result<Vec<u8>, String>>;
+}
+
+/// Fake KMS keeping its key in memory, standing for a remote service.
+struct InMemoryKms {
+    key: JWK,
+}
+
+impl KmsBackend for InMemoryKms {
+    fn sign<'a>(
+        &'a self,
+        algorithm: ES256OrES384,
+        message: &'a [u8],
+    ) -> BoxFuture<'a, Result<Vec<u8>, String>> {
+        Box::pin(async move {
+            let algorithm = match algorithm {
+                ES256OrES384::ES256 => Algorithm::ES256,
+                ES256OrES384::ES384 => Algorithm::ES384,
+            };
+
+            jws::sign_bytes(algorithm, message, &self.key).map_err(|e| e.to_string())
+        })
+    }
+}
+
+/// Signer delegating to a boxed KMS backend.
+#[derive(Clone)]
+struct KmsSigner(Arc<dyn KmsBackend>);
+
+impl Signer<Multikey> for KmsSigner {
+    type MessageSigner = Self;
+
+    async fn for_method(
+        &self,
+        _method: Cow<'_, Multikey>,
+    ) -> Result<Option<Self::MessageSigner>, SignatureError> {
+        // A real implementation would check that the KMS holds the private key
+        // of the given verification method.
+        Ok(Some(self.clone()))
+    }
+}
+
+impl MessageSigner<ES256OrES384> for KmsSigner {
+    async fn sign(
+        self,
+        algorithm: ES256OrES384,
+        message: &[u8],
+    ) -> Result<Vec<u8>, MessageSignatureError> {
+        self.0
+            .sign(algorithm, message)
+            .await
+            .map_err(MessageSignatureError::SignatureFailed)
+    }
+}
+
+#[async_std::main]
+async fn main() {
+    let key = JWK::generate_p256();
+
+    let vm = DIDKey::generate_url(&key.to_public()).unwrap();
+    let options = ProofOptions::from_method(vm.into_iri().into());
+
+    let params = SignatureParameters::new(
+        AnyDidMethod::default().into_vm_resolver(),
+        KmsSigner(Arc::new(InMemoryKms { key })),
+        None,
+    );
+
+    let vc = w3c_vc_barcodes::create(
+        &MRZ_DATA,
+        uri!("http://example.org/issuer").to_owned(),
+        MachineReadableZone {},
+        options,
+        params,
+    )
+    .await
+    .unwrap();
+
+    let bytes = encode_to_bytes(&vc).await;
+    println!("{}", MachineReadableZone::encode_qr_code_payload(&bytes))
+}
@@ -1,8 +1,14 @@
-use std::io;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::io;
 
 use super::{
     mandatory_data_elements, optional_data_elements,
-    pdf_417::{read_array, DecodeSubfile, RecordEntry, Subfile},
+    pdf_417::{
+        read_array, DecodeSubfile, RecordEntry, RecordEntryRef, Subfile, SubfileParseError,
+        SubfileParseErrorKind,
+    },
     MissingDataElement,
 };
 
@@ -19,12 +25,32 @@ impl DlElement {
             .or_else(|| DlOptionalElement::from_id(id).map(Self::Optional))
     }
 
+    /// Parses a three-letter element identifier (e.g. `"DAC"`).
+    pub fn from_code(code: &str) -> Option<Self> {
+        DlMandatoryElement::from_code(code)
+            .map(Self::Mandatory)
+            .or_else(|| DlOptionalElement::from_code(code).map(Self::Optional))
+    }
+
     pub fn id(&self) -> &'static [u8; 3] {
         match self {
             Self::Mandatory(e) => e.id(),
             Self::Optional(e) => e.id(),
         }
     }
+
+    pub fn string_id(&self) -> &'static str {
+        match self {
+            Self::Mandatory(e) => e.string_id(),
+            Self::Optional(e) => e.string_id(),
+        }
+    }
+}
+
+impl fmt::Display for DlElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.string_id())
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -63,6 +89,146 @@ impl DlSubfile {
                     .map(|(k, v)| (DlElement::Optional(k), v)),
             )
     }
+
+    /// Compares this subfile with `other`, element by element.
+    ///
+    /// Elements only present in `self` are reported as
+    /// [`FieldDiff::Removed`], elements only present in `other` as
+    /// [`FieldDiff::Added`]. Mandatory elements are always present, so they
+    /// can only be [`FieldDiff::Changed`]. Differences follow the [`Self::iter`]
+    /// order of `self`, then of `other` for added elements.
+    pub fn diff(&self, other: &DlSubfile) -> Vec<FieldDiff> {
+        let mut result = Vec::new();
+
+        for (element, value) in self.iter() {
+            match other.get(element) {
+                Some(other_value) if other_value == value => (),
+                Some(other_value) => result.push(FieldDiff::Changed {
+                    element,
+                    old: value.to_vec(),
+                    new: other_value.to_vec(),
+                }),
+                None => result.push(FieldDiff::Removed {
+                    element,
+                    value: value.to_vec(),
+                }),
+            }
+        }
+
+        for (element, value) in other.iter() {
+            if self.get(element).is_none() {
+                result.push(FieldDiff::Added {
+                    element,
+                    value: value.to_vec(),
+                })
+            }
+        }
+
+        result
+    }
+
+    /// Decodes a `DL` subfile without copying its values.
+    ///
+    /// The returned view borrows every value from `bytes`, which must start
+    /// with the `DL` subfile type. All mandatory elements must be present.
+    pub fn borrowed(bytes: &[u8]) -> io::Result<DlSubfileRef<'_>> {
+        let mut rest = bytes
+            .strip_prefix(b"DL")
+            .ok_or(io::ErrorKind::InvalidData)?;
+        let mut entries = Vec::new();
+
+        loop {
+            let (entry, last, tail) = RecordEntryRef::decode(rest)?;
+            let element = DlElement::from_id(&entry.field).ok_or(io::ErrorKind::InvalidData)?;
+            entries.push((element, entry.value));
+            rest = tail;
+
+            if last {
+                break;
+            }
+        }
+
+        for e in DlMandatoryElement::LIST {
+            if !entries.iter().any(|(k, _)| *k == DlElement::Mandatory(e)) {
+                return Err(MissingDataElement(e).into());
+            }
+        }
+
+        Ok(DlSubfileRef { entries })
+    }
+}
+
+/// Difference between two `DL` subfiles on a given element.
+///
+/// See [`DlSubfile::diff`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldDiff {
+    /// Element only present in the other subfile.
+    Added { element: DlElement, value: Vec<u8> },
+
+    /// Element missing from the other subfile.
+    Removed { element: DlElement, value: Vec<u8> },
+
+    /// Element present in both subfiles, with different values.
+    Changed {
+        element: DlElement,
+        old: Vec<u8>,
+        new: Vec<u8>,
+    },
+}
+
+impl FieldDiff {
+    pub fn element(&self) -> DlElement {
+        match self {
+            Self::Added { element, .. }
+            | Self::Removed { element, .. }
+            | Self::Changed { element, .. } => *element,
+        }
+    }
+}
+
+/// Borrowed view over an encoded `DL` subfile.
+///
+/// See [`DlSubfile::borrowed`].
+#[derive(Debug, Clone)]
+pub struct DlSubfileRef<'a> {
+    entries: Vec<(DlElement, &'a [u8])>,
+}
+
+impl<'a> DlSubfileRef<'a> {
+    /// Returns the number of elements.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Checks if the subfile has no element.
+    ///
+    /// This is never the case for a subfile returned by
+    /// [`DlSubfile::borrowed`], which contains every mandatory element.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the value of the given element, if present.
+    ///
+    /// If the element appears more than once, the last value is returned.
+    pub fn get(&self, element: DlElement) -> Option<&'a [u8]> {
+        self.entries
+            .iter()
+            .rev()
+            .find(|(k, _)| *k == element)
+            .map(|(_, v)| *v)
+    }
+
+    /// Returns the value of the given mandatory element.
+    pub fn mandatory(&self, element: DlMandatoryElement) -> &'a [u8] {
+        self.get(DlElement::Mandatory(element)).unwrap()
+    }
+
+    /// Iterates over the elements, in encoding order.
+    pub fn iter(&self) -> impl '_ + Iterator<Item = (DlElement, &'a [u8])> {
+        self.entries.iter().copied()
+    }
 }
 
 #[derive(Debug, Default)]
@@ -92,16 +258,22 @@ impl DlSubfileBuilder {
 }
 
 impl DecodeSubfile for DlSubfile {
-    fn decode_subfile(reader: &mut impl std::io::prelude::BufRead) -> std::io::Result<Self> {
+    fn decode_subfile(reader: &mut impl io::BufRead) -> io::Result<Self> {
         if read_array(reader)? != *b"DL" {
             return Err(io::ErrorKind::InvalidData.into());
         }
 
         let mut builder = DlSubfileBuilder::new();
+        let mut offset = 2;
 
         loop {
-            let (entry, last) = RecordEntry::decode(reader)?;
-            let element = DlElement::from_id(&entry.field).ok_or(io::ErrorKind::InvalidData)?;
+            let entry_offset = offset;
+            let (entry, last) = RecordEntry::decode_at(reader, &mut offset)?;
+            let element = DlElement::from_id(&entry.field).ok_or(SubfileParseError {
+                offset: entry_offset,
+                field: Some(entry.field),
+                kind: SubfileParseErrorKind::UnknownField,
+            })?;
             builder.set(element, entry.value);
 
             if last {
@@ -111,13 +283,20 @@ impl DecodeSubfile for DlSubfile {
     }
 }
 
+impl TryFrom<&Subfile> for DlSubfile {
+    type Error = io::Error;
+
+    fn try_from(value: &Subfile) -> io::Result<Self> {
+        value.decode()
+    }
+}
+
 impl From<DlSubfile> for Subfile {
     fn from(value: DlSubfile) -> Self {
         let last = value.len() - 1;
         let mut data = Vec::new();
-        let mut cursor = io::Cursor::new(&mut data);
         for (i, (e, v)) in value.iter().enumerate() {
-            RecordEntry::encode_ref(&mut cursor, e.id(), v, i == last).unwrap();
+            RecordEntry::encode_ref(&mut data, e.id(), v, i == last).unwrap();
         }
 
         Self::new(*b"DL", data)
@@ -127,70 +306,70 @@ impl From<DlSubfile> for Subfile {
 mandatory_data_elements! {
     pub enum DlMandatoryElement, struct DlMandatoryElements (DlMandatoryElementsBuilder) {
         /// Customer ID Number (DAQ).
-        customer_id_number: V25Ans => CustomerIdNumber: b"DAQ",
+        customer_id_number (set_customer_id_number): V25Ans => CustomerIdNumber: b"DAQ",
 
         /// Customer Family Name (DCS).
-        customer_family_name: V40Ans => CustomerFamilyName: b"DCS",
+        customer_family_name (set_customer_family_name): V40Ans => CustomerFamilyName: b"DCS",
 
         /// Family name truncation (DDE).
-        family_name_truncation: F1A => FamilyNameTruncation: b"DDE",
+        family_name_truncation (set_family_name_truncation): F1A => FamilyNameTruncation: b"DDE",
 
         /// Customer First Name (DAC).
-        customer_first_name: V40Ans => CustomerFirstName: b"DAC",
+        customer_first_name (set_customer_first_name): V40Ans => CustomerFirstName: b"DAC",
 
         /// First name truncation (DDF).
-        first_name_truncation: F1A => FirstNameTruncation: b"DDF",
+        first_name_truncation (set_first_name_truncation): F1A => FirstNameTruncation: b"DDF",
 
         /// Customer Middle Name(s) (DAD).
-        customer_middle_name: V40Ans => CustomerMiddleName: b"DAD",
+        customer_middle_name (set_customer_middle_name): V40Ans => CustomerMiddleName: b"DAD",
 
         /// Middle name truncation (DDG).
-        middle_name_truncation: F1A => MiddleNameTruncation: b"DDG",
+        middle_name_truncation (set_middle_name_truncation): F1A => MiddleNameTruncation: b"DDG",
 
         /// Jurisdiction-specific vehicle class (DCA).
-        vehicle_class: V6Ans => VehicleClass: b"DCA",
+        vehicle_class (set_vehicle_class): V6Ans => VehicleClass: b"DCA",
 
         /// Jurisdiction-specific restriction codes (DCB).
-        restriction_codes: V12Ans => RestrictionCodes: b"DCB",
+        restriction_codes (set_restriction_codes): V12Ans => RestrictionCodes: b"DCB",
 
         /// Jurisdiction-specific endorsement codes (DCD).
-        endorsement_codes: V5Ans => EndorsementCodes: b"DCD",
+        endorsement_codes (set_endorsement_codes): V5Ans => EndorsementCodes: b"DCD",
 
         /// Document Issue Date (DBD).
-        document_issue_date: F8N => DocumentIssueDate: b"DBD",
+        document_issue_date (set_document_issue_date): F8N => DocumentIssueDate: b"DBD",
 
         /// Date of Birth (DBB).
-        date_of_birth: F8N => DateOfBirth: b"DBB",
+        date_of_birth (set_date_of_birth): F8N => DateOfBirth: b"DBB",
 
         /// Document Expiration Date (DBA).
-        document_expiration_date: F8N => DocumentExpirationDate: b"DBA",
+        document_expiration_date (set_document_expiration_date): F8N => DocumentExpirationDate: b"DBA",
 
         /// Physical Description – Sex (DBC).
-        sex: F1N => Sex: b"DBC",
+        sex (set_sex): F1N => Sex: b"DBC",
 
         /// Physical Description – Height (DAU).
-        height: F6Ans => Height: b"DAU",
+        height (set_height): F6Ans => Height: b"DAU",
 
         /// Physical Description – Eye Color (DAY).
-        eye_color: F3A => EyeColor: b"DAY",
+        eye_color (set_eye_color): F3A => EyeColor: b"DAY",
 
         /// Address – Street 1 (DAG).
-        address_street_1: V35Ans => AddressStreet1: b"DAG",
+        address_street_1 (set_address_street_1): V35Ans => AddressStreet1: b"DAG",
 
         /// Address – City (DAI).
-        address_city: V20Ans => AddressCity: b"DAI",
+        address_city (set_address_city): V20Ans => AddressCity: b"DAI",
 
         /// Address – Jurisdiction Code (DAJ).
-        address_jurisdiction_code: F2A => AddressJurisdictionCode: b"DAJ",
+        address_jurisdiction_code (set_address_jurisdiction_code): F2A => AddressJurisdictionCode: b"DAJ",
 
         /// Address – Postal Code (DAK).
-        address_postal_code: F11Ans => AddressPostalCode: b"DAK",
+        address_postal_code (set_address_postal_code): F11Ans => AddressPostalCode: b"DAK",
 
         /// Document Discriminator (DCF).
-        document_discriminator: V25Ans => DocumentDiscriminator: b"DCF",
+        document_discriminator (set_document_discriminator): V25Ans => DocumentDiscriminator: b"DCF",
 
         /// Country Identification (DCG).
-        country_identification: F3A => CountryIdentification: b"DCG"
+        country_identification (set_country_identification): F3A => CountryIdentification: b"DCG"
     }
 }
 
@@ -1,8 +1,13 @@
-use std::io;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::io;
 
 use super::{
     mandatory_data_elements, optional_data_elements,
-    pdf_417::{read_array, DecodeSubfile, RecordEntry, Subfile},
+    pdf_417::{
+        read_array, DecodeSubfile, RecordEntry, Subfile, SubfileParseError, SubfileParseErrorKind,
+    },
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -18,14 +23,35 @@ impl IdElement {
             .or_else(|| IdOptionalElement::from_id(id).map(Self::Optional))
     }
 
+    /// Parses a three-letter element identifier (e.g. `"DAC"`).
+    pub fn from_code(code: &str) -> Option<Self> {
+        IdMandatoryElement::from_code(code)
+            .map(Self::Mandatory)
+            .or_else(|| IdOptionalElement::from_code(code).map(Self::Optional))
+    }
+
     pub fn id(&self) -> &'static [u8; 3] {
         match self {
             Self::Mandatory(e) => e.id(),
             Self::Optional(e) => e.id(),
         }
     }
+
+    pub fn string_id(&self) -> &'static str {
+        match self {
+            Self::Mandatory(e) => e.string_id(),
+            Self::Optional(e) => e.string_id(),
+        }
+    }
+}
+
+impl fmt::Display for IdElement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.string_id())
+    }
 }
 
+#[derive(Debug, Clone)]
 pub struct IdSubfile {
     pub mandatory: IdMandatoryElements,
     pub optional: IdOptionalElements,
@@ -50,7 +76,7 @@ impl IdSubfile {
 }
 
 impl DecodeSubfile for IdSubfile {
-    fn decode_subfile(reader: &mut impl std::io::prelude::BufRead) -> std::io::Result<Self> {
+    fn decode_subfile(reader: &mut impl io::BufRead) -> io::Result<Self> {
         let mut mandatory = IdMandatoryElementsBuilder::new();
         let mut optional = IdOptionalElements::new();
 
@@ -58,10 +84,19 @@ impl DecodeSubfile for IdSubfile {
             return Err(io::ErrorKind::InvalidData.into());
         }
 
+        let mut offset = 2;
+
         loop {
-            let (entry, last) = RecordEntry::decode(reader)?;
+            let entry_offset = offset;
+            let (entry, last) = RecordEntry::decode_at(reader, &mut offset)?;
+
+            let element = IdElement::from_id(&entry.field).ok_or(SubfileParseError {
+                offset: entry_offset,
+                field: Some(entry.field),
+                kind: SubfileParseErrorKind::UnknownField,
+            })?;
 
-            match IdElement::from_id(&entry.field).ok_or(io::ErrorKind::InvalidData)? {
+            match element {
                 IdElement::Mandatory(e) => mandatory.set(e, entry.value),
                 IdElement::Optional(e) => {
                     optional.set(e, Some(entry.value));
@@ -78,77 +113,84 @@ impl DecodeSubfile for IdSubfile {
     }
 }
 
+impl TryFrom<&Subfile> for IdSubfile {
+    type Error = io::Error;
+
+    fn try_from(value: &Subfile) -> io::Result<Self> {
+        value.decode()
+    }
+}
+
 impl From<IdSubfile> for Subfile {
     fn from(value: IdSubfile) -> Self {
         let last = value.len() - 1;
         let mut data = Vec::new();
-        let mut cursor = io::Cursor::new(&mut data);
         for (i, (e, v)) in value.iter().enumerate() {
-            RecordEntry::encode_ref(&mut cursor, e.id(), v, i == last).unwrap();
+            RecordEntry::encode_ref(&mut data, e.id(), v, i == last).unwrap();
         }
 
-        Self::new(*b"DL", data)
+        Self::new(*b"ID", data)
     }
 }
 
 mandatory_data_elements! {
     pub enum IdMandatoryElement, struct IdMandatoryElements (IdMandatoryElementsBuilder) {
         /// Document Expiration Date (DBA).
-        document_expiration_date: F8N => DocumentExpirationDate: b"DBA",
+        document_expiration_date (set_document_expiration_date): F8N => DocumentExpirationDate: b"DBA",
 
         /// Customer Family Name (DCS).
-        customer_family_name: V40Ans => CustomerFamilyName: b"DCS",
+        customer_family_name (set_customer_family_name): V40Ans => CustomerFamilyName: b"DCS",
 
         /// Customer First Name (DAC).
-        customer_first_name: V40Ans => CustomerFirstName: b"DAC",
+        customer_first_name (set_customer_first_name): V40Ans => CustomerFirstName: b"DAC",
 
         /// Customer Middle Name(s) (DAD).
-        customer_middle_name: V40Ans => CustomerMiddleName: b"DAD",
+        customer_middle_name (set_customer_middle_name): V40Ans => CustomerMiddleName: b"DAD",
 
         /// Document Issue Date (DBD).
-        document_issue_date: F8N => DocumentIssueDate: b"DBD",
+        document_issue_date (set_document_issue_date): F8N => DocumentIssueDate: b"DBD",
 
         /// Date of Birth (DBB).
-        date_of_birth: F8N => DateOfBirth: b"DBB",
+        date_of_birth (set_date_of_birth): F8N => DateOfBirth: b"DBB",
 
         /// Physical Description – Sex (DBC).
-        sex: F1N => Sex: b"DBC",
+        sex (set_sex): F1N => Sex: b"DBC",
 
         /// Physical Description – Eye Color (DAY).
-        eye_color: F3A => EyeColor: b"DAY",
+        eye_color (set_eye_color): F3A => EyeColor: b"DAY",
 
         /// Physical Description – Height (DAU).
-        height: F6Ans => Height: b"DAU",
+        height (set_height): F6Ans => Height: b"DAU",
 
         /// Address – Street 1 (DAG).
-        address_street_1: V35Ans => AddressStreet1: b"DAG",
+        address_street_1 (set_address_street_1): V35Ans => AddressStreet1: b"DAG",
 
         /// Address – City (DAI).
-        address_city: V20Ans => AddressCity: b"DAI",
+        address_city (set_address_city): V20Ans => AddressCity: b"DAI",
 
         /// Address – Jurisdiction Code (DAJ).
-        address_jurisdiction_code: F2A => AddressJurisdictionCode: b"DAJ",
+        address_jurisdiction_code (set_address_jurisdiction_code): F2A => AddressJurisdictionCode: b"DAJ",
 
         /// Address – Postal Code (DAK).
-        address_postal_code: F11Ans => AddressPostalCode: b"DAK",
+        address_postal_code (set_address_postal_code): F11Ans => AddressPostalCode: b"DAK",
 
         /// Customer ID Number (DAQ).
-        customer_id_number: V25Ans => CustomerIdNumber: b"DAQ",
+        customer_id_number (set_customer_id_number): V25Ans => CustomerIdNumber: b"DAQ",
 
         /// Document Discriminator (DCF).
-        document_discriminator: V25Ans => DocumentDiscriminator: b"DCF",
+        document_discriminator (set_document_discriminator): V25Ans => DocumentDiscriminator: b"DCF",
 
         /// Country Identification (DCG).
-        country_identification: F3A => CountryIdentification: b"DCG",
+        country_identification (set_country_identification): F3A => CountryIdentification: b"DCG",
 
         /// Family name truncation (DDE).
-        family_name_truncation: F1A => FamilyNameTruncation: b"DDE",
+        family_name_truncation (set_family_name_truncation): F1A => FamilyNameTruncation: b"DDE",
 
         /// First name truncation (DDF).
-        first_name_truncation: F1A => FirstNameTruncation: b"DDF",
+        first_name_truncation (set_first_name_truncation): F1A => FirstNameTruncation: b"DDF",
 
         /// Middle name truncation (DDG).
-        middle_name_truncation: F1A => MiddleNameTruncation: b"DDG"
+        middle_name_truncation (set_middle_name_truncation): F1A => MiddleNameTruncation: b"DDG"
     }
 }
 
@@ -230,3 +272,54 @@ optional_data_elements! {
         veteran_indicator: F1N => VeteranIndicator: b"DDL"
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::{IdMandatoryElement, IdMandatoryElementsBuilder, IdSubfile};
+    use crate::aamva::dlid::{
+        pdf_417::{DecodeSubfile, Subfile},
+        types::{V25Ans, F8N},
+    };
+
+    const ID_SUBFILE_BYTES: &str = "IDDACJOHN\nDADNONE\nDAG123 MAIN ST\nDAIANYVILLE\nDAJUTO\nDAKF87P20000\nDAQF987654321\nDAU069 IN\nDAYBRO\nDBA04192030\nDBB04191988\nDBC1\nDBD01012024\nDCFUTODOCDISCRIM\nDCGUTO\nDCSSMITH\nDDEN\nDDFN\nDDGN\r";
+
+    #[test]
+    fn id_subfile_round_trip() {
+        let id = IdSubfile::decode_subfile_from_bytes(ID_SUBFILE_BYTES.as_bytes()).unwrap();
+        let subfile = Subfile::from(id.clone());
+        assert_eq!(subfile.subfile_type, *b"ID");
+        assert_eq!(Subfile::from(id), subfile.clone());
+
+        let mut bytes = Vec::new();
+        subfile.write(&mut bytes).unwrap();
+
+        let id = IdSubfile::decode_subfile_from_bytes(&bytes).unwrap();
+        let subfile = Subfile::from(id);
+        assert_eq!(subfile.subfile_type, *b"ID");
+
+        let mut bytes2 = Vec::new();
+        subfile.write(&mut bytes2).unwrap();
+        assert_eq!(bytes, bytes2)
+    }
+
+    #[test]
+    fn typed_setters() {
+        let mut builder = IdMandatoryElementsBuilder::new();
+        builder
+            .set_date_of_birth(F8N::new("04191988").unwrap())
+            .set_customer_id_number(V25Ans::new("F987654321").unwrap());
+        assert_eq!(
+            builder.get(IdMandatoryElement::DateOfBirth),
+            Some(b"04191988".as_slice())
+        );
+        assert_eq!(
+            builder.get(IdMandatoryElement::CustomerIdNumber),
+            Some(b"F987654321".as_slice())
+        );
+
+        assert!(F8N::new("041919880").is_err());
+        assert!(F8N::new("O4191988").is_err())
+    }
+}
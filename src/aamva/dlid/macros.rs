@@ -25,8 +25,19 @@ macro_rules! data_elements_enum {
 		}
 
 		impl $enum_id {
-			pub fn string_id(&self) -> &str {
-				unsafe { std::str::from_utf8_unchecked(self.id()) }
+			pub fn string_id(&self) -> &'static str {
+				unsafe { core::str::from_utf8_unchecked(self.id()) }
+			}
+
+			/// Parses a three-letter element identifier (e.g. `"DAC"`).
+			pub fn from_code(code: &str) -> Option<Self> {
+				<&[u8; 3]>::try_from(code.as_bytes()).ok().and_then(Self::from_id)
+			}
+		}
+
+		impl core::fmt::Display for $enum_id {
+			fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+				f.write_str(self.string_id())
 			}
 		}
 	};
@@ -39,16 +50,16 @@ macro_rules! data_elements_enum {
 }
 
 macro_rules! mandatory_data_elements {
-	($(#[$enum_meta:meta])* $vis:vis enum $enum_id:ident, struct $struct_id:ident ($partial_id:ident) { $($(#[$meta:meta])* $field:ident : $ty:ident => $id:ident : $tag:literal),* }) => {
+	($(#[$enum_meta:meta])* $vis:vis enum $enum_id:ident, struct $struct_id:ident ($partial_id:ident) { $($(#[$meta:meta])* $field:ident ($setter:ident) : $ty:ident => $id:ident : $tag:literal),* }) => {
 		$crate::aamva::dlid::data_elements_enum!($(#[$enum_meta])* $vis enum $enum_id { $($(#[$meta])* $id : $tag),* });
 
 		#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 		$vis struct $struct_id {
-			$($(#[$meta])* pub $field: Vec<u8>),*
+			$($(#[$meta])* pub $field: alloc::vec::Vec<u8>),*
 		}
 
 		impl $struct_id {
-			pub fn new_with<'a>(mut f: impl FnMut($enum_id) -> std::borrow::Cow<'a, [u8]>) -> Self {
+			pub fn new_with<'a>(mut f: impl FnMut($enum_id) -> alloc::borrow::Cow<'a, [u8]>) -> Self {
 				Self {
 					$($field: f($enum_id::$id).into_owned()),*
 				}
@@ -60,7 +71,7 @@ macro_rules! mandatory_data_elements {
 				}
 			}
 
-			pub fn set(&mut self, element: $enum_id, value: Vec<u8>) {
+			pub fn set(&mut self, element: $enum_id, value: alloc::vec::Vec<u8>) {
 				match element {
 					$($enum_id::$id => { self.$field = value }),*
 				}
@@ -69,11 +80,49 @@ macro_rules! mandatory_data_elements {
 			pub fn iter(&self) -> impl Iterator<Item = ($enum_id, &[u8])> {
 				[$(($enum_id::$id, self.$field.as_slice())),*].into_iter()
 			}
+
+			/// Collects the given elements, failing if any is missing.
+			///
+			/// If an element is given more than once, the last value is kept.
+			pub fn try_from_iter(iter: impl IntoIterator<Item = ($enum_id, alloc::vec::Vec<u8>)>) -> Result<Self, $crate::aamva::dlid::MissingDataElement<$enum_id>> {
+				iter.into_iter().collect::<$partial_id>().build()
+			}
+		}
+
+		/// Missing elements are left empty. Use `try_from_iter` to reject them
+		/// instead.
+		impl FromIterator<($enum_id, alloc::vec::Vec<u8>)> for $struct_id {
+			fn from_iter<T: IntoIterator<Item = ($enum_id, alloc::vec::Vec<u8>)>>(iter: T) -> Self {
+				let partial: $partial_id = iter.into_iter().collect();
+				Self {
+					$($field: partial.$field.unwrap_or_default()),*
+				}
+			}
+		}
+
+		impl IntoIterator for $struct_id {
+			type Item = ($enum_id, alloc::vec::Vec<u8>);
+			type IntoIter = core::array::IntoIter<Self::Item, { $enum_id::COUNT }>;
+
+			fn into_iter(self) -> Self::IntoIter {
+				[$(($enum_id::$id, self.$field)),*].into_iter()
+			}
+		}
+
+		/// Typed accessors, validating the value against the declared field
+		/// type.
+		impl $struct_id {
+			$(
+				$(#[$meta])*
+				pub fn $field(&self) -> Result<$crate::aamva::dlid::types::$ty, $crate::aamva::dlid::types::InvalidFieldValue> {
+					$crate::aamva::dlid::types::$ty::new(&self.$field)
+				}
+			)*
 		}
 
 		#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 		$vis struct $partial_id {
-			$($(#[$meta])* pub $field: Option<Vec<u8>>),*
+			$($(#[$meta])* pub $field: Option<alloc::vec::Vec<u8>>),*
 		}
 
 		impl $partial_id {
@@ -87,7 +136,7 @@ macro_rules! mandatory_data_elements {
 				}
 			}
 
-			pub fn set(&mut self, element: $enum_id, value: Vec<u8>) {
+			pub fn set(&mut self, element: $enum_id, value: alloc::vec::Vec<u8>) {
 				match element {
 					$($enum_id::$id => { self.$field = Some(value) }),*
 				}
@@ -99,6 +148,31 @@ macro_rules! mandatory_data_elements {
 				})
 			}
 		}
+
+		/// Typed setters, only accepting values already validated against the
+		/// declared field type. They are named `set_<field>`, apart from the
+		/// public fields.
+		impl $partial_id {
+			$(
+				$(#[$meta])*
+				pub fn $setter(&mut self, value: $crate::aamva::dlid::types::$ty) -> &mut Self {
+					self.$field = Some(value.as_bytes().to_vec());
+					self
+				}
+			)*
+		}
+
+		impl FromIterator<($enum_id, alloc::vec::Vec<u8>)> for $partial_id {
+			fn from_iter<T: IntoIterator<Item = ($enum_id, alloc::vec::Vec<u8>)>>(iter: T) -> Self {
+				let mut result = Self::new();
+
+				for (element, value) in iter {
+					result.set(element, value)
+				}
+
+				result
+			}
+		}
 	}
 }
 
@@ -108,7 +182,7 @@ macro_rules! optional_data_elements {
 
 		#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 		$vis struct $struct_id {
-			$($(#[$meta])* pub $field: Option<Vec<u8>>),*
+			$($(#[$meta])* pub $field: Option<alloc::vec::Vec<u8>>),*
 		}
 
 		impl $struct_id {
@@ -144,7 +218,7 @@ macro_rules! optional_data_elements {
 				}
 			}
 
-			pub fn set(&mut self, element: $enum_id, value: Option<Vec<u8>>) {
+			pub fn set(&mut self, element: $enum_id, value: Option<alloc::vec::Vec<u8>>) {
 				match element {
 					$($enum_id::$id => { self.$field = value }),*
 				}
@@ -158,6 +232,17 @@ macro_rules! optional_data_elements {
 				),*].into_iter().flatten()
 			}
 		}
+
+		/// Typed accessors, validating the value against the declared field
+		/// type.
+		impl $struct_id {
+			$(
+				$(#[$meta])*
+				pub fn $field(&self) -> Result<Option<$crate::aamva::dlid::types::$ty>, $crate::aamva::dlid::types::InvalidFieldValue> {
+					self.$field.as_ref().map($crate::aamva::dlid::types::$ty::new).transpose()
+				}
+			)*
+		}
 	}
 }
 
@@ -2,16 +2,26 @@
 ///
 /// See: <https://www.aamva.org/assets/best-practices,-guides,-standards,-manuals,-whitepapers/aamva-dl-id-card-design-standard-(2020)>
 mod macros;
-use std::io;
+use core::fmt;
 
 pub(crate) use macros::*;
 
+use crate::io;
+
 pub mod types;
 
-#[derive(Debug, thiserror::Error)]
-#[error("missing data element `{0}`")]
+#[derive(Debug)]
 pub struct MissingDataElement<T>(pub T);
 
+impl<T: fmt::Display> fmt::Display for MissingDataElement<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "missing data element `{}`", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: fmt::Debug + fmt::Display> std::error::Error for MissingDataElement<T> {}
+
 impl<T> From<MissingDataElement<T>> for io::Error {
     fn from(_value: MissingDataElement<T>) -> Self {
         io::ErrorKind::InvalidData.into()
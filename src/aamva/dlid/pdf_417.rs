@@ -1,4 +1,9 @@
-use std::io::{self, BufRead, Seek};
+use alloc::{collections::BTreeMap, format, string::String, vec, vec::Vec};
+use core::fmt;
+
+use crate::io::{self, BufRead, Seek};
+
+use super::{DlSubfile, IdSubfile};
 
 const HEADER_SIZE: u64 = 9 + 6 + 2 + 2 + 2;
 
@@ -10,7 +15,16 @@ const RECORD_SEPARATOR: u8 = 0x1e;
 
 const SEGMENT_TERMINATOR: u8 = b'\r';
 
-const PREFIX: [u8; 9] = *b"@\n\x1e\rANSI ";
+/// Line feed some encoders emit after the segment terminator.
+const LINE_FEED: u8 = b'\n';
+
+const PREFIX: [u8; 4] = *b"@\n\x1e\r";
+
+/// Latest AAMVA version number (AAMVA DL/ID-2020).
+const MAX_VERSION: u8 = 10;
+
+/// Maximum number of subfiles accepted by [`Header::decode_checked`].
+const MAX_ENTRY_COUNT: u8 = 20;
 
 pub struct FileBuilder {
     header: Header,
@@ -21,6 +35,7 @@ impl FileBuilder {
     pub fn new(issuer_id: u32, version: u8, jurisdiction_version: u8) -> Self {
         Self {
             header: Header {
+                compliance_indicator: ComplianceIndicator::default(),
                 issuer_id,
                 version,
                 jurisdiction_version,
@@ -30,19 +45,112 @@ impl FileBuilder {
         }
     }
 
+    /// Creates a builder holding the header and subfiles of the given file.
+    ///
+    /// Subfiles are copied as is, without decoding them. This allows
+    /// appending a subfile (e.g. a `ZZ` VCB subfile) to an existing barcode
+    /// without knowing the schema of the other subfiles. The subfile
+    /// designators are recomputed when writing.
+    pub fn from_file<R: BufRead + Seek>(file: &mut File<R>) -> io::Result<Self> {
+        let mut subfiles = Vec::with_capacity(file.subfile_count());
+        for i in 0..file.subfile_count() {
+            let subfile_type = file.subfile_designators[i].subfile_type;
+            let bytes = file.subfile_bytes(i)?;
+            match bytes.split_first_chunk() {
+                Some((t, data)) if *t == subfile_type => {
+                    subfiles.push(Subfile::new(subfile_type, data.to_vec()))
+                }
+                _ => {
+                    return Err(invalid_data(format!(
+                        "subfile `{}` does not start with its type",
+                        String::from_utf8_lossy(&subfile_type)
+                    )))
+                }
+            }
+        }
+
+        Ok(Self {
+            header: file.header(),
+            subfiles,
+        })
+    }
+
+    /// Sets the compliance indicator written in the file header.
+    ///
+    /// Defaults to [`ComplianceIndicator::Ansi`].
+    pub fn set_compliance_indicator(&mut self, compliance_indicator: ComplianceIndicator) {
+        self.header.compliance_indicator = compliance_indicator
+    }
+
+    /// Adds a subfile to the file.
+    ///
+    /// Each subfile type must appear at most once in the file, otherwise
+    /// [`Self::write`] will fail.
     pub fn push(&mut self, subfile: impl Into<Subfile>) {
         self.subfiles.push(subfile.into());
     }
 
-    pub fn write(mut self, writer: &mut impl io::Write) -> io::Result<()> {
-        self.header.entry_count = self.subfiles.len() as u8;
+    /// Adds the driver license (`DL`) subfile.
+    pub fn push_dl(&mut self, subfile: DlSubfile) {
+        self.push(subfile)
+    }
+
+    /// Adds the identification card (`ID`) subfile.
+    pub fn push_id(&mut self, subfile: IdSubfile) {
+        self.push(subfile)
+    }
+
+    /// Adds the VCB (`ZZ`) subfile.
+    #[cfg(feature = "std")]
+    pub fn push_zz(&mut self, subfile: crate::aamva::ZZSubfile) {
+        self.push(subfile)
+    }
+
+    pub fn write(self, writer: &mut impl io::Write) -> io::Result<()> {
+        self.write_streaming(writer, &[])?.finish()
+    }
+
+    /// Writes the file without holding the data of every subfile in memory.
+    ///
+    /// `streamed` lists the type and data length (excluding the subfile type)
+    /// of subfiles following the pushed ones. This writes the file header,
+    /// the subfile designators and the pushed subfiles, then returns a
+    /// [`StreamingFileWriter`] to write the streamed subfiles data, in order.
+    ///
+    /// The output is byte-identical to [`Self::write`] with the same
+    /// subfiles pushed in the same order.
+    pub fn write_streaming<'w, W: io::Write>(
+        mut self,
+        writer: &'w mut W,
+        streamed: &[([u8; 2], u64)],
+    ) -> io::Result<StreamingFileWriter<'w, W>> {
+        let designators: Vec<_> = self
+            .subfiles
+            .iter()
+            .map(|s| (s.subfile_type, s.data.len() as u64))
+            .chain(streamed.iter().copied())
+            .collect();
+
+        for (i, (subfile_type, _)) in designators.iter().enumerate() {
+            if designators[..i].iter().any(|(t, _)| t == subfile_type) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "duplicate subfile type `{}`",
+                        String::from_utf8_lossy(subfile_type)
+                    ),
+                ));
+            }
+        }
+
+        self.header.entry_count = designators.len() as u8;
         self.header.encode(writer)?;
 
-        let mut offset = HEADER_SIZE + SUBFILE_DESIGNATOR_SIZE * self.subfiles.len() as u64;
-        for subfile in &self.subfiles {
-            let length = 2u64 + subfile.data.len() as u64;
+        let mut offset = header_end(designators.len());
+        for (subfile_type, data_len) in designators {
+            let length = 2u64 + data_len;
             SubfileDesignator {
-                subfile_type: subfile.subfile_type,
+                subfile_type,
                 offset,
                 length,
             }
@@ -54,17 +162,97 @@ impl FileBuilder {
             subfile.write(writer)?;
         }
 
-        Ok(())
+        Ok(StreamingFileWriter {
+            writer,
+            subfiles: streamed.to_vec(),
+            next: 0,
+            remaining: 0,
+        })
     }
 
+    /// Writes the file into a byte buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a subfile type appears more than once. See [`Self::push`].
     pub fn into_bytes(self) -> Vec<u8> {
         let mut result = Vec::new();
-        let mut cursor = io::Cursor::new(&mut result);
-        self.write(&mut cursor).unwrap();
+        self.write(&mut result).unwrap();
         result
     }
 }
 
+/// Writes the data of streamed subfiles.
+///
+/// See [`FileBuilder::write_streaming`].
+pub struct StreamingFileWriter<'w, W> {
+    writer: &'w mut W,
+    subfiles: Vec<([u8; 2], u64)>,
+    next: usize,
+    remaining: u64,
+}
+
+impl<W: io::Write> StreamingFileWriter<'_, W> {
+    /// Starts writing the next subfile, returning its type.
+    ///
+    /// Fails if the data of the current subfile is incomplete, or if every
+    /// subfile has already been started.
+    pub fn begin_subfile(&mut self) -> io::Result<[u8; 2]> {
+        self.check_complete()?;
+
+        let (subfile_type, data_len) = *self.subfiles.get(self.next).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "no more subfiles to write")
+        })?;
+
+        write_array(self.writer, subfile_type)?;
+        self.next += 1;
+        self.remaining = data_len;
+        Ok(subfile_type)
+    }
+
+    /// Writes data of the current subfile.
+    ///
+    /// Fails if the data exceeds the declared subfile length.
+    pub fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+        if data.len() as u64 > self.remaining {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "subfile data exceeds its declared length",
+            ));
+        }
+
+        self.writer.write_all(data)?;
+        self.remaining -= data.len() as u64;
+        Ok(())
+    }
+
+    /// Checks that the data of every subfile has been written.
+    pub fn finish(self) -> io::Result<()> {
+        self.check_complete()?;
+
+        if self.next < self.subfiles.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "missing subfiles",
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn check_complete(&self) -> io::Result<()> {
+        if self.remaining > 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "subfile data is shorter than its declared length",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Subfile {
     pub subfile_type: [u8; 2],
     pub data: Vec<u8>,
@@ -79,18 +267,78 @@ impl Subfile {
         write_array(writer, self.subfile_type)?;
         writer.write_all(&self.data)
     }
+
+    /// Decodes this subfile into a typed subfile.
+    ///
+    /// Fails with [`io::ErrorKind::InvalidData`] if the subfile type does not
+    /// match the typed subfile.
+    pub fn decode<T: DecodeSubfile>(&self) -> io::Result<T> {
+        let mut bytes = Vec::with_capacity(2 + self.data.len());
+        bytes.extend_from_slice(&self.subfile_type);
+        bytes.extend_from_slice(&self.data);
+        T::decode_subfile_from_bytes(&bytes)
+    }
 }
 
 pub struct File<'a, R> {
     header: Header,
     subfile_designators: Vec<SubfileDesignator>,
+
+    /// Stream position right after the subfile designators, including any
+    /// bytes consumed by [`File::new_lenient`] while looking for more.
+    designators_end: u64,
+
     reader: &'a mut R,
 }
 
 impl<'a, R: BufRead> File<'a, R> {
     pub fn new(reader: &'a mut R) -> io::Result<Self> {
         let header = Header::decode(reader)?;
+        Self::with_header(header, reader)
+    }
 
+    /// Reads the file header and subfile designators, tolerating an
+    /// `entry_count` that does not match the actual number of designators.
+    ///
+    /// Some encoders declare more or fewer subfiles than they write. Instead
+    /// of trusting the header, designators are read until the offset of the
+    /// first subfile is reached, or until the next bytes do not look like a
+    /// designator (two uppercase letters followed by a 4-digit offset and
+    /// length). The bytes of a rejected designator are consumed, and skipped
+    /// by [`File::read_subfiles_sequential`].
+    ///
+    /// No warning is logged on a count mismatch, as this crate does not log:
+    /// the [`File::header`] still holds the declared `entry_count`, compare it
+    /// with [`File::subfile_count`] to report the discrepancy.
+    ///
+    /// Use [`File::new`] for strict conformance.
+    pub fn new_lenient(reader: &'a mut R) -> io::Result<Self> {
+        let header = Header::decode(reader)?;
+
+        let mut subfile_designators = Vec::new();
+        let mut designators_end = header_end(0);
+        let mut data_start = u64::MAX;
+
+        // No designator fits before the data.
+        while designators_end + SUBFILE_DESIGNATOR_SIZE <= data_start {
+            match read_designator_lenient(reader, &mut designators_end)? {
+                Some(desc) => {
+                    data_start = data_start.min(desc.offset);
+                    subfile_designators.push(desc)
+                }
+                None => break,
+            }
+        }
+
+        Ok(Self {
+            header,
+            subfile_designators,
+            designators_end,
+            reader,
+        })
+    }
+
+    fn with_header(header: Header, reader: &'a mut R) -> io::Result<Self> {
         let entry_count = header.entry_count as usize;
         let mut subfile_designators = Vec::with_capacity(entry_count);
         for _ in 0..entry_count {
@@ -100,6 +348,7 @@ impl<'a, R: BufRead> File<'a, R> {
         Ok(Self {
             header,
             subfile_designators,
+            designators_end: header_end(entry_count),
             reader,
         })
     }
@@ -108,14 +357,133 @@ impl<'a, R: BufRead> File<'a, R> {
         self.header
     }
 
+    /// Number of subfile designators actually read.
+    ///
+    /// This is the header `entry_count`, unless the file was read with
+    /// [`File::new_lenient`].
+    pub fn subfile_count(&self) -> usize {
+        self.subfile_designators.len()
+    }
+
     pub fn index_of(&self, subfile_type: &[u8; 2]) -> Option<usize> {
         self.subfile_designators
             .iter()
             .position(|d| d.subfile_type == *subfile_type)
     }
+
+    /// Reads the requested subfiles in a single forward pass, without
+    /// seeking.
+    ///
+    /// The reader must be positioned right after the subfile designators, as
+    /// left by [`File::new`] or [`File::new_lenient`]. Subfiles are read in offset order, skipping the
+    /// bytes in between. Requested subfile types absent from the file are
+    /// absent from the result.
+    pub fn read_subfiles_sequential<D: DecodeSubfile>(
+        &mut self,
+        wanted: &[[u8; 2]],
+    ) -> io::Result<BTreeMap<[u8; 2], D>> {
+        let mut designators: Vec<_> = self
+            .subfile_designators
+            .iter()
+            .filter(|d| wanted.contains(&d.subfile_type))
+            .collect();
+        designators.sort_by_key(|d| d.offset);
+
+        let mut position = self.designators_end;
+        let mut result = BTreeMap::new();
+        for desc in designators {
+            if desc.offset < position {
+                return Err(invalid_data(format!(
+                    "subfile `{}` offset {} overlaps previous data ending at {position}",
+                    String::from_utf8_lossy(&desc.subfile_type),
+                    desc.offset
+                )));
+            }
+
+            skip(self.reader, desc.offset - position)?;
+
+            let mut data = vec![0; desc.length as usize];
+            self.reader.read_exact(&mut data)?;
+            result.insert(desc.subfile_type, D::decode_subfile_from_bytes(&data)?);
+
+            position = desc.end();
+        }
+
+        Ok(result)
+    }
+}
+
+/// Reads the next subfile designator, if the following bytes look like one.
+///
+/// The designator bytes are consumed even if they do not form a designator,
+/// and added to `position`. Returns `None` if the stream ends before a whole
+/// designator is read.
+fn read_designator_lenient(
+    reader: &mut impl BufRead,
+    position: &mut u64,
+) -> io::Result<Option<SubfileDesignator>> {
+    let mut bytes = [0; SUBFILE_DESIGNATOR_SIZE as usize];
+    let mut len = 0;
+
+    // The reader may buffer less than a designator at a time.
+    while len < bytes.len() {
+        let available = reader.fill_buf()?;
+        if available.is_empty() {
+            return Ok(None);
+        }
+
+        let amt = available.len().min(bytes.len() - len);
+        bytes[len..len + amt].copy_from_slice(&available[..amt]);
+        reader.consume(amt);
+        *position += amt as u64;
+        len += amt;
+    }
+
+    if !bytes[..2].iter().all(u8::is_ascii_uppercase) {
+        return Ok(None);
+    }
+
+    Ok(SubfileDesignator::decode(&mut io::Cursor::new(&bytes[..])).ok())
+}
+
+/// Skips the next `n` bytes of the given reader.
+fn skip(reader: &mut impl BufRead, mut n: u64) -> io::Result<()> {
+    while n > 0 {
+        let available = reader.fill_buf()?.len() as u64;
+        if available == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        let amt = available.min(n);
+        reader.consume(amt as usize);
+        n -= amt;
+    }
+
+    Ok(())
 }
 
 impl<'a, R: BufRead + Seek> File<'a, R> {
+    /// Reads the file header and subfile designators, and checks that every
+    /// designator points inside the stream.
+    ///
+    /// Unlike [`File::new`], which defers all checks until a subfile is read,
+    /// this makes sure that the header is plausible (see
+    /// [`Header::decode_checked`]), and that designators are ordered, do not
+    /// overlap, and do not extend past the end of the stream.
+    pub fn new_checked(reader: &'a mut R) -> io::Result<Self> {
+        let header = Header::decode_checked(reader)?;
+        let mut file = Self::with_header(header, reader)?;
+        file.validate()?;
+        Ok(file)
+    }
+
+    /// Checks that the subfile designators are ordered, do not overlap, and
+    /// stay within the stream length.
+    pub fn validate(&mut self) -> io::Result<()> {
+        let stream_len = stream_len(self.reader)?;
+        validate_designators(&self.subfile_designators, stream_len)
+    }
+
     pub fn read_subfile<D: DecodeSubfile>(
         &mut self,
         subfile_type: &[u8; 2],
@@ -127,21 +495,75 @@ impl<'a, R: BufRead + Seek> File<'a, R> {
     }
 
     pub fn read_subfile_by_index<D: DecodeSubfile>(&mut self, index: usize) -> io::Result<D> {
-        let desc = &self.subfile_designators[index];
+        let desc = self
+            .subfile_designators
+            .get(index)
+            .ok_or_else(|| invalid_data(format!("no subfile designator at index {index}")))?;
+        let stream_len = stream_len(self.reader)?;
+        desc.validate(header_end(self.subfile_designators.len()), stream_len)?;
         self.reader.seek(io::SeekFrom::Start(desc.offset))?;
         D::decode_subfile(self.reader)
     }
+
+    /// Returns the raw bytes of the subfile at the given index, as delimited
+    /// by its designator offset and length.
+    ///
+    /// The bytes start with the two-letter subfile type, and are not decoded
+    /// in any way.
+    pub fn subfile_bytes(&mut self, index: usize) -> io::Result<Vec<u8>> {
+        let desc = self
+            .subfile_designators
+            .get(index)
+            .ok_or_else(|| invalid_data(format!("no subfile designator at index {index}")))?;
+        let stream_len = stream_len(self.reader)?;
+        desc.validate(header_end(self.subfile_designators.len()), stream_len)?;
+        self.reader.seek(io::SeekFrom::Start(desc.offset))?;
+
+        let mut data = vec![0; desc.length as usize];
+        self.reader.read_exact(&mut data)?;
+        Ok(data)
+    }
+}
+
+fn stream_len(reader: &mut impl Seek) -> io::Result<u64> {
+    let position = reader.stream_position()?;
+    let len = reader.seek(io::SeekFrom::End(0))?;
+    if position != len {
+        reader.seek(io::SeekFrom::Start(position))?;
+    }
+
+    Ok(len)
+}
+
+/// Offset of the first byte following the header and subfile designators.
+fn header_end(entry_count: usize) -> u64 {
+    HEADER_SIZE + SUBFILE_DESIGNATOR_SIZE * entry_count as u64
+}
+
+fn validate_designators(designators: &[SubfileDesignator], stream_len: u64) -> io::Result<()> {
+    let mut start = header_end(designators.len());
+    for desc in designators {
+        desc.validate(start, stream_len)?;
+        start = desc.end();
+    }
+
+    Ok(())
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
 }
 
 pub trait DecodeSubfile: Sized {
     fn decode_subfile(reader: &mut impl BufRead) -> io::Result<Self>;
 
-    fn decode_subfile_from_bytes(bytes: &[u8]) -> std::io::Result<Self> {
+    fn decode_subfile_from_bytes(bytes: &[u8]) -> io::Result<Self> {
         let mut cursor = io::Cursor::new(bytes);
         Self::decode_subfile(&mut cursor)
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Record {
     subfile_type: [u8; 2],
     entries: Vec<RecordEntry>,
@@ -159,13 +581,26 @@ impl Record {
     }
 }
 
-impl DecodeSubfile for Record {
-    fn decode_subfile(reader: &mut impl BufRead) -> io::Result<Self> {
+impl Record {
+    /// Decodes a record, rejecting the line feed some encoders emit after the
+    /// segment terminator.
+    ///
+    /// Meant for spec-conformance testing. [`DecodeSubfile::decode_subfile`]
+    /// tolerates such line feeds.
+    pub fn decode_subfile_strict(reader: &mut impl BufRead) -> io::Result<Self> {
+        Self::decode_with(reader, RecordEntry::decode_strict_at)
+    }
+
+    fn decode_with<R: BufRead>(
+        reader: &mut R,
+        decode_entry: impl Fn(&mut R, &mut usize) -> Result<(RecordEntry, bool), SubfileParseError>,
+    ) -> io::Result<Self> {
         let subfile_type = read_array(reader)?;
         let mut entries = Vec::new();
+        let mut offset = 2;
 
         loop {
-            let (entry, last) = RecordEntry::decode(reader)?;
+            let (entry, last) = decode_entry(reader, &mut offset)?;
             entries.push(entry);
 
             if last {
@@ -178,11 +613,16 @@ impl DecodeSubfile for Record {
     }
 }
 
+impl DecodeSubfile for Record {
+    fn decode_subfile(reader: &mut impl BufRead) -> io::Result<Self> {
+        Self::decode_with(reader, RecordEntry::decode_at)
+    }
+}
+
 impl From<Record> for Subfile {
     fn from(value: Record) -> Self {
         let mut data = Vec::new();
-        let mut cursor = io::Cursor::new(&mut data);
-        value.write_entries(&mut cursor).unwrap();
+        value.write_entries(&mut data).unwrap();
 
         Subfile {
             subfile_type: value.subfile_type,
@@ -199,14 +639,49 @@ impl DecodeSubfile for Vec<u8> {
             let b = read_u8(reader)?;
             result.push(b);
             if b == SEGMENT_TERMINATOR {
+                skip_line_feed(reader)?;
                 break Ok(result);
             }
         }
     }
 }
 
+/// File type marker following the compliance prefix in the file header.
+///
+/// The marker is not determined by the header AAMVA version: legacy `AAMVA`
+/// markers are found on version `01` barcodes as well as on older ones.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ComplianceIndicator {
+    /// `ANSI `, the file type required by the AAMVA DL/ID Card Design
+    /// Standard. Use it unless targeting readers expecting legacy barcodes.
+    #[default]
+    Ansi,
+
+    /// `AAMVA`, found on legacy barcodes, typically with an AAMVA version of
+    /// `00` or `01`.
+    Aamva,
+}
+
+impl ComplianceIndicator {
+    pub fn from_bytes(bytes: &[u8; 5]) -> Option<Self> {
+        match bytes {
+            b"ANSI " => Some(Self::Ansi),
+            b"AAMVA" => Some(Self::Aamva),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> &'static [u8; 5] {
+        match self {
+            Self::Ansi => b"ANSI ",
+            Self::Aamva => b"AAMVA",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Header {
+    pub compliance_indicator: ComplianceIndicator,
     pub issuer_id: u32,
     pub version: u8,
     pub jurisdiction_version: u8,
@@ -214,12 +689,43 @@ pub struct Header {
 }
 
 impl Header {
+    /// Returns the name of the issuing jurisdiction, if its IIN is known.
+    ///
+    /// See [`crate::aamva::iin`].
+    pub fn jurisdiction_name(&self) -> Option<&'static str> {
+        crate::aamva::iin::jurisdiction_name(self.issuer_id)
+    }
+
+    /// Returns the card design standard revision the barcode conforms to.
+    ///
+    /// Only the AAMVA version is considered: the jurisdiction version is
+    /// jurisdiction-specific, with no published registry. See
+    /// [`Header::is_newer_than`] to compare both.
+    pub fn card_design_profile(&self) -> CardDesignProfile {
+        CardDesignProfile::from_version(self.version)
+    }
+
+    /// Checks if the barcode comes from a newer card design than the given
+    /// AAMVA and jurisdiction versions.
+    ///
+    /// Jurisdictions bump the jurisdiction version when they revise their
+    /// field layout, which may change which optional elements are present. A
+    /// parser can use this to flag barcodes more recent than the latest
+    /// design it was tested against for this jurisdiction.
+    pub fn is_newer_than(&self, version: u8, jurisdiction_version: u8) -> bool {
+        (self.version, self.jurisdiction_version) > (version, jurisdiction_version)
+    }
+
     pub fn decode(reader: &mut impl BufRead) -> io::Result<Self> {
         if read_array(reader)? != PREFIX {
             return Err(io::ErrorKind::InvalidData.into());
         }
 
+        let compliance_indicator = ComplianceIndicator::from_bytes(&read_array(reader)?)
+            .ok_or(io::ErrorKind::InvalidData)?;
+
         Ok(Self {
+            compliance_indicator,
             issuer_id: decode_digits6(read_array(reader)?)?,
             version: decode_digits2(read_array(reader)?)?,
             jurisdiction_version: decode_digits2(read_array(reader)?)?,
@@ -227,8 +733,51 @@ impl Header {
         })
     }
 
+    /// Decodes the header, and checks that its fields are plausible.
+    ///
+    /// On top of what [`Header::decode`] checks, this makes sure that:
+    /// - the AAMVA version is known (at most `10`) and consistent with the
+    ///   compliance indicator,
+    /// - the file has between 1 and 20 subfiles.
+    pub fn decode_checked(reader: &mut impl BufRead) -> io::Result<Self> {
+        let header = Self::decode(reader)?;
+        header.validate()?;
+        Ok(header)
+    }
+
+    /// Checks that the header fields are plausible.
+    ///
+    /// See [`Header::decode_checked`].
+    pub fn validate(&self) -> io::Result<()> {
+        if self.version > MAX_VERSION {
+            return Err(invalid_data(format!(
+                "unknown AAMVA version {:02}",
+                self.version
+            )));
+        }
+
+        let legacy = self.compliance_indicator == ComplianceIndicator::Aamva;
+        if legacy != (self.version == 0) {
+            return Err(invalid_data(format!(
+                "AAMVA version {:02} is inconsistent with compliance indicator `{}`",
+                self.version,
+                String::from_utf8_lossy(self.compliance_indicator.as_bytes())
+            )));
+        }
+
+        if self.entry_count == 0 || self.entry_count > MAX_ENTRY_COUNT {
+            return Err(invalid_data(format!(
+                "invalid number of subfiles {} (expected 1 to {MAX_ENTRY_COUNT})",
+                self.entry_count
+            )));
+        }
+
+        Ok(())
+    }
+
     pub fn encode(&self, writer: &mut impl io::Write) -> io::Result<()> {
         writer.write_all(&PREFIX)?;
+        writer.write_all(self.compliance_indicator.as_bytes())?;
         write_array(writer, encode_digits6(self.issuer_id))?;
         write_array(writer, encode_digits2(self.version))?;
         write_array(writer, encode_digits2(self.jurisdiction_version))?;
@@ -237,6 +786,55 @@ impl Header {
     }
 }
 
+/// AAMVA DL/ID Card Design Standard revision, given by the header version.
+///
+/// See [`Header::card_design_profile`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardDesignProfile {
+    /// Barcode predating the first standard (version `00`).
+    Legacy,
+
+    /// Known standard revision.
+    Standard {
+        /// AAMVA version number.
+        version: u8,
+
+        /// Year of the standard revision (e.g. `2020` for AAMVA DL/ID-2020).
+        year: u16,
+    },
+
+    /// Revision newer than the ones known by this crate. Its elements may not
+    /// be fully understood.
+    Unknown(u8),
+}
+
+impl CardDesignProfile {
+    pub fn from_version(version: u8) -> Self {
+        let year = match version {
+            0 => return Self::Legacy,
+            1 => 2000,
+            2 => 2003,
+            3 => 2005,
+            4 => 2009,
+            5 => 2010,
+            6 => 2011,
+            7 => 2012,
+            8 => 2013,
+            9 => 2016,
+            10 => 2020,
+            _ => return Self::Unknown(version),
+        };
+
+        Self::Standard { version, year }
+    }
+
+    /// Checks if the revision is known by this crate.
+    pub fn is_known(&self) -> bool {
+        !matches!(self, Self::Unknown(_))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct SubfileDesignator {
     pub subfile_type: [u8; 2],
     pub offset: u64,
@@ -252,6 +850,33 @@ impl SubfileDesignator {
         })
     }
 
+    /// Offset of the first byte following this subfile.
+    pub fn end(&self) -> u64 {
+        self.offset + self.length
+    }
+
+    /// Checks that this subfile starts at or after `start` and ends before
+    /// `stream_len`.
+    fn validate(&self, start: u64, stream_len: u64) -> io::Result<()> {
+        let subfile_type = String::from_utf8_lossy(&self.subfile_type);
+
+        if self.offset < start {
+            return Err(invalid_data(format!(
+                "subfile `{subfile_type}` offset {} overlaps previous data ending at {start}",
+                self.offset
+            )));
+        }
+
+        if self.end() > stream_len {
+            return Err(invalid_data(format!(
+                "subfile `{subfile_type}` (offset {}, length {}) extends past the end of the stream ({stream_len} bytes)",
+                self.offset, self.length
+            )));
+        }
+
+        Ok(())
+    }
+
     pub fn encode(&self, writer: &mut impl io::Write) -> io::Result<()> {
         write_array(writer, self.subfile_type)?;
         write_array(writer, encode_digits4(self.offset))?;
@@ -330,33 +955,223 @@ fn encode_digits6(value: u32) -> [u8; 6] {
 
 fn read_u8(reader: &mut impl BufRead) -> io::Result<u8> {
     let mut value = 0;
-    reader.read_exact(std::slice::from_mut(&mut value))?;
+    reader.read_exact(core::slice::from_mut(&mut value))?;
     Ok(value)
 }
 
+/// Consumes the line feed following a segment terminator, if any.
+///
+/// Returns whether a line feed was consumed.
+fn skip_line_feed(reader: &mut impl BufRead) -> io::Result<bool> {
+    if reader.fill_buf()?.first() == Some(&LINE_FEED) {
+        reader.consume(1);
+        Ok(true)
+    } else {
+        Ok(false)
+    }
+}
+
+/// Subfile record parse error, locating the malformed byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubfileParseError {
+    /// Offset of the byte at which the error occurred, from the start of the
+    /// subfile (its two-byte type).
+    pub offset: usize,
+
+    /// Field being read, if its identifier was read already.
+    pub field: Option<[u8; 3]>,
+
+    pub kind: SubfileParseErrorKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubfileParseErrorKind {
+    /// Record separator inside a data element.
+    UnexpectedRecordSeparator,
+
+    /// Unknown data element identifier.
+    UnknownField,
+
+    /// Read error, such as an unexpected end of file.
+    Io(io::ErrorKind),
+}
+
+impl SubfileParseError {
+    fn io(offset: usize, field: Option<[u8; 3]>, e: io::Error) -> Self {
+        Self {
+            offset,
+            field,
+            kind: SubfileParseErrorKind::Io(e.kind()),
+        }
+    }
+}
+
+impl fmt::Display for SubfileParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            SubfileParseErrorKind::UnexpectedRecordSeparator => {
+                write!(f, "unexpected record separator")?
+            }
+            SubfileParseErrorKind::UnknownField => write!(f, "unknown data element")?,
+            SubfileParseErrorKind::Io(io::ErrorKind::UnexpectedEof) => {
+                write!(f, "unexpected end of file")?
+            }
+            SubfileParseErrorKind::Io(kind) => write!(f, "read error ({kind:?})")?,
+        }
+
+        write!(f, " at offset {}", self.offset)?;
+
+        if let Some(field) = &self.field {
+            write!(
+                f,
+                " while reading field {}",
+                core::str::from_utf8(field).unwrap_or("???")
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SubfileParseError {}
+
+impl From<SubfileParseError> for io::Error {
+    fn from(value: SubfileParseError) -> Self {
+        let kind = match value.kind {
+            SubfileParseErrorKind::Io(kind) => kind,
+            _ => io::ErrorKind::InvalidData,
+        };
+
+        io::Error::new(kind, format!("{value}"))
+    }
+}
+
 fn write_u8(writer: &mut impl io::Write, value: u8) -> io::Result<()> {
-    writer.write_all(std::slice::from_ref(&value))
+    writer.write_all(core::slice::from_ref(&value))
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RecordEntry {
     pub field: [u8; 3],
     pub value: Vec<u8>,
 }
 
+/// Record entry borrowing its value from the source buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecordEntryRef<'a> {
+    pub field: [u8; 3],
+    pub value: &'a [u8],
+}
+
+impl<'a> RecordEntryRef<'a> {
+    /// Decodes the record entry at the start of `bytes`.
+    ///
+    /// Returns the entry, whether it is the last of its subfile, and the
+    /// remaining bytes. A line feed following the segment terminator is
+    /// skipped.
+    pub fn decode(bytes: &'a [u8]) -> io::Result<(Self, bool, &'a [u8])> {
+        if bytes.len() < 3 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        let (field, rest) = bytes.split_at(3);
+        let end = rest
+            .iter()
+            .position(|b| {
+                matches!(
+                    *b,
+                    DATA_ELEMENT_SEPARATOR | RECORD_SEPARATOR | SEGMENT_TERMINATOR
+                )
+            })
+            .ok_or(io::ErrorKind::UnexpectedEof)?;
+
+        let last = match rest[end] {
+            DATA_ELEMENT_SEPARATOR => false,
+            SEGMENT_TERMINATOR => true,
+            _ => return Err(io::ErrorKind::InvalidData.into()),
+        };
+
+        let entry = Self {
+            field: [field[0], field[1], field[2]],
+            value: &rest[..end],
+        };
+
+        let mut rest = &rest[end + 1..];
+        if last {
+            rest = rest.strip_prefix(&[LINE_FEED]).unwrap_or(rest);
+        }
+
+        Ok((entry, last, rest))
+    }
+}
+
 impl RecordEntry {
+    /// Decodes a record entry, returning it along with whether it is the last
+    /// of its subfile.
+    ///
+    /// Some encoders terminate segments with `\r\n` instead of `\r`. The line
+    /// feed following the segment terminator is consumed, so that it does not
+    /// corrupt the next read. Use [`RecordEntry::decode_strict`] to leave it
+    /// in the reader.
     pub fn decode(reader: &mut impl io::BufRead) -> io::Result<(Self, bool)> {
-        let field: [u8; 3] = read_array(reader)?;
+        Self::decode_at(reader, &mut 0).map_err(Into::into)
+    }
+
+    /// Decodes a record entry, strictly following the specification.
+    pub fn decode_strict(reader: &mut impl io::BufRead) -> io::Result<(Self, bool)> {
+        Self::decode_strict_at(reader, &mut 0).map_err(Into::into)
+    }
+
+    /// Decodes a record entry starting at the given subfile offset, like
+    /// [`RecordEntry::decode`].
+    ///
+    /// The offset is advanced past the entry, and used to locate errors.
+    pub fn decode_at(
+        reader: &mut impl io::BufRead,
+        offset: &mut usize,
+    ) -> Result<(Self, bool), SubfileParseError> {
+        let (entry, last) = Self::decode_strict_at(reader, offset)?;
+
+        if last && skip_line_feed(reader).map_err(|e| SubfileParseError::io(*offset, None, e))? {
+            *offset += 1;
+        }
+
+        Ok((entry, last))
+    }
+
+    /// Decodes a record entry starting at the given subfile offset, like
+    /// [`RecordEntry::decode_strict`].
+    ///
+    /// The offset is advanced past the entry, and used to locate errors.
+    pub fn decode_strict_at(
+        reader: &mut impl io::BufRead,
+        offset: &mut usize,
+    ) -> Result<(Self, bool), SubfileParseError> {
+        let field: [u8; 3] =
+            read_array(reader).map_err(|e| SubfileParseError::io(*offset, None, e))?;
+        *offset += 3;
         let mut value = Vec::new();
 
         let last = loop {
-            match read_u8(reader)? {
+            let b = read_u8(reader).map_err(|e| SubfileParseError::io(*offset, Some(field), e))?;
+            match b {
                 DATA_ELEMENT_SEPARATOR => break false,
-                RECORD_SEPARATOR => return Err(io::ErrorKind::InvalidData.into()),
+                RECORD_SEPARATOR => {
+                    return Err(SubfileParseError {
+                        offset: *offset,
+                        field: Some(field),
+                        kind: SubfileParseErrorKind::UnexpectedRecordSeparator,
+                    })
+                }
                 SEGMENT_TERMINATOR => break true,
                 b => value.push(b),
             }
+
+            *offset += 1;
         };
 
+        *offset += 1;
         Ok((Self { field, value }, last))
     }
 
@@ -1,15 +1,40 @@
-use std::{fmt, io, marker::PhantomData, ops::Deref};
-
-#[derive(Debug, thiserror::Error)]
-#[error("invalid field value: {0}")]
+//! DL/ID data element value types.
+//!
+//! Element values are either fixed-length ([`Fixed`]) or variable-length with
+//! a maximum length ([`Variable`]), over a character class. The type aliases
+//! follow the notation of the AAMVA data element tables: `F` (fixed) or `V`
+//! (variable), the (maximum) length, then the character class:
+//!   - `A`: alphabetic ([`Alpha`]),
+//!   - `N`: numeric ([`Numeric`]),
+//!   - `An`: alphanumeric ([`AlphaNumeric`]),
+//!   - `Ans`: alphanumeric and special characters ([`AlphaNumericSpecial`]).
+//!
+//! For instance, `F8N` is an 8-digit date, and `V40Ans` a name of at most 40
+//! characters. Aliases are grouped by length kind then character class, and
+//! sorted by length.
+use alloc::{borrow::ToOwned, vec::Vec};
+use core::{fmt, marker::PhantomData, ops::Deref};
+
+use crate::io;
+
+#[derive(Debug)]
 pub struct InvalidFieldValue(MaybeAscii);
 
+impl fmt::Display for InvalidFieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid field value: {}", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidFieldValue {}
+
 struct MaybeAscii(Vec<u8>);
 
 impl fmt::Display for MaybeAscii {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if self.0.iter().all(u8::is_ascii) {
-            write!(f, "{:?}", std::str::from_utf8(&self.0).unwrap())
+            write!(f, "{:?}", core::str::from_utf8(&self.0).unwrap())
         } else {
             write!(f, "{:?}", self.0)
         }
@@ -19,7 +44,7 @@ impl fmt::Display for MaybeAscii {
 impl fmt::Debug for MaybeAscii {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if self.0.iter().all(u8::is_ascii) {
-            write!(f, "{:?}", std::str::from_utf8(&self.0).unwrap())
+            write!(f, "{:?}", core::str::from_utf8(&self.0).unwrap())
         } else {
             write!(f, "{:?}", self.0)
         }
@@ -103,6 +128,15 @@ impl<C: CharClass, const N: usize> Fixed<C, N> {
         })
     }
 
+    /// Replaces the value of this field.
+    ///
+    /// The new value is checked against the length and character class of
+    /// the field. On error, the field is left unchanged.
+    pub fn set(&mut self, value: impl AsRef<[u8]>) -> Result<(), InvalidFieldValue> {
+        *self = Self::new(value)?;
+        Ok(())
+    }
+
     pub fn as_bytes(&self) -> &[u8] {
         &self.data
     }
@@ -111,7 +145,7 @@ impl<C: CharClass, const N: usize> Fixed<C, N> {
         unsafe {
             // SAFETY: the character class `C` ensures that all bytes are in the
             //         ASCII range.
-            std::str::from_utf8_unchecked(self.as_bytes())
+            core::str::from_utf8_unchecked(self.as_bytes())
         }
     }
 }
@@ -153,6 +187,34 @@ impl<C: CharClass, const N: usize> Variable<C, N> {
         })
     }
 
+    /// Replaces the value of this field.
+    ///
+    /// The new value is checked against the capacity and character class of
+    /// the field. On error, the field is left unchanged.
+    pub fn set(&mut self, value: impl AsRef<[u8]>) -> Result<(), InvalidFieldValue> {
+        *self = Self::new(value)?;
+        Ok(())
+    }
+
+    /// Appends the given value at the end of this field.
+    ///
+    /// Fails if the appended value contains characters outside the field's
+    /// character class, or if the result would exceed the field capacity `N`.
+    /// On error, the field is left unchanged.
+    pub fn push_str(&mut self, value: impl AsRef<[u8]>) -> Result<(), InvalidFieldValue> {
+        let bytes = value.as_ref();
+        let len = self.len + bytes.len();
+        if len > N || !bytes.iter().copied().all(C::contains) {
+            let mut value = self.as_bytes().to_owned();
+            value.extend_from_slice(bytes);
+            return Err(InvalidFieldValue(MaybeAscii(value)));
+        }
+
+        self.data[self.len..len].copy_from_slice(bytes);
+        self.len = len;
+        Ok(())
+    }
+
     pub fn as_bytes(&self) -> &[u8] {
         &self.data[..self.len]
     }
@@ -161,7 +223,7 @@ impl<C: CharClass, const N: usize> Variable<C, N> {
         unsafe {
             // SAFETY: the character class `C` ensures that all bytes are in the
             //         ASCII range.
-            std::str::from_utf8_unchecked(self.as_bytes())
+            core::str::from_utf8_unchecked(self.as_bytes())
         }
     }
 }
@@ -174,18 +236,128 @@ impl<C: CharClass, const N: usize> Deref for Variable<C, N> {
     }
 }
 
+/// Borrowed fixed-length field value.
+///
+/// Same as [`Fixed`], but validating a slice of the source buffer instead of
+/// copying it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FixedRef<'a, C: CharClass, const N: usize> {
+    data: &'a [u8],
+    class: PhantomData<C>,
+}
+
+impl<'a, C: CharClass, const N: usize> FixedRef<'a, C, N> {
+    pub fn new(bytes: &'a [u8]) -> Result<Self, InvalidFieldValue> {
+        if bytes.len() != N || !bytes.iter().copied().all(C::contains) {
+            return Err(InvalidFieldValue(MaybeAscii(bytes.to_owned())));
+        }
+
+        Ok(Self {
+            data: bytes,
+            class: PhantomData,
+        })
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    pub fn as_str(&self) -> &'a str {
+        unsafe {
+            // SAFETY: the character class `C` ensures that all bytes are in the
+            //         ASCII range.
+            core::str::from_utf8_unchecked(self.data)
+        }
+    }
+
+    pub fn to_owned(&self) -> Fixed<C, N> {
+        let mut data = [0u8; N];
+        data.copy_from_slice(self.data);
+        Fixed {
+            data,
+            class: PhantomData,
+        }
+    }
+}
+
+impl<C: CharClass, const N: usize> Deref for FixedRef<'_, C, N> {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+/// Borrowed variable-length field value.
+///
+/// Same as [`Variable`], but validating a slice of the source buffer instead
+/// of copying it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VariableRef<'a, C: CharClass, const N: usize> {
+    data: &'a [u8],
+    class: PhantomData<C>,
+}
+
+impl<'a, C: CharClass, const N: usize> VariableRef<'a, C, N> {
+    pub fn new(bytes: &'a [u8]) -> Result<Self, InvalidFieldValue> {
+        if bytes.len() > N || !bytes.iter().copied().all(C::contains) {
+            return Err(InvalidFieldValue(MaybeAscii(bytes.to_owned())));
+        }
+
+        Ok(Self {
+            data: bytes,
+            class: PhantomData,
+        })
+    }
+
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    pub fn as_str(&self) -> &'a str {
+        unsafe {
+            // SAFETY: the character class `C` ensures that all bytes are in the
+            //         ASCII range.
+            core::str::from_utf8_unchecked(self.data)
+        }
+    }
+
+    pub fn to_owned(&self) -> Variable<C, N> {
+        let mut data = [0u8; N];
+        data[..self.data.len()].copy_from_slice(self.data);
+        Variable {
+            data,
+            len: self.data.len(),
+            class: PhantomData,
+        }
+    }
+}
+
+impl<C: CharClass, const N: usize> Deref for VariableRef<'_, C, N> {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
 pub type F1A = Fixed<Alpha, 1>;
 pub type F2A = Fixed<Alpha, 2>;
 pub type F3A = Fixed<Alpha, 3>;
 
 pub type F1N = Fixed<Numeric, 1>;
+pub type F2N = Fixed<Numeric, 2>;
 pub type F3N = Fixed<Numeric, 3>;
 pub type F8N = Fixed<Numeric, 8>;
+pub type F9N = Fixed<Numeric, 9>;
 
 pub type F4An = Fixed<AlphaNumeric, 4>;
 pub type F5An = Fixed<AlphaNumeric, 5>;
+pub type F10An = Fixed<AlphaNumeric, 10>;
+pub type F11An = Fixed<AlphaNumeric, 11>;
 pub type F12An = Fixed<AlphaNumeric, 12>;
 
+pub type F4Ans = Fixed<AlphaNumericSpecial, 4>;
 pub type F6Ans = Fixed<AlphaNumericSpecial, 6>;
 pub type F11Ans = Fixed<AlphaNumericSpecial, 11>;
 
@@ -203,3 +375,46 @@ pub type V25Ans = Variable<AlphaNumericSpecial, 25>;
 pub type V35Ans = Variable<AlphaNumericSpecial, 35>;
 pub type V40Ans = Variable<AlphaNumericSpecial, 40>;
 pub type V50Ans = Variable<AlphaNumericSpecial, 50>;
+pub type V125Ans = Variable<AlphaNumericSpecial, 125>;
+
+#[cfg(test)]
+mod tests {
+    use super::{AlphaNumericSpecial, FixedRef, Numeric, V5Ans, VariableRef, F2A};
+
+    #[test]
+    fn set_fixed() {
+        let mut value = F2A::new("UT").unwrap();
+        value.set("CA").unwrap();
+        assert_eq!(value.as_str(), "CA");
+        assert!(value.set("C").is_err());
+        assert!(value.set("C1").is_err());
+        assert_eq!(value.as_str(), "CA")
+    }
+
+    #[test]
+    fn push_variable() {
+        let mut value = V5Ans::new("JR").unwrap();
+        value.push_str(".").unwrap();
+        assert_eq!(value.as_str(), "JR.");
+        assert!(value.push_str("abc").is_err());
+        assert!(value.push_str("\u{e9}").is_err());
+        value.push_str("II").unwrap();
+        assert_eq!(value.as_str(), "JR.II");
+        value.set("SR").unwrap();
+        assert_eq!(value.as_str(), "SR")
+    }
+
+    #[test]
+    fn borrowed() {
+        let bytes = b"04192030SMITH";
+        let date = FixedRef::<Numeric, 8>::new(&bytes[..8]).unwrap();
+        assert_eq!(date.as_str(), "04192030");
+        assert_eq!(date.to_owned().as_str(), "04192030");
+        assert!(FixedRef::<Numeric, 8>::new(&bytes[..7]).is_err());
+
+        let name = VariableRef::<AlphaNumericSpecial, 40>::new(&bytes[8..]).unwrap();
+        assert_eq!(name.as_bytes().as_ptr(), bytes[8..].as_ptr());
+        assert_eq!(name.to_owned().as_str(), "SMITH");
+        assert!(VariableRef::<AlphaNumericSpecial, 4>::new(&bytes[8..]).is_err())
+    }
+}
@@ -0,0 +1,268 @@
+//! ISO/IEC 18013-5 mobile driving licence (mDL) interoperability.
+//!
+//! An mDL carries the same cardholder data as the PDF417 barcode, in the
+//! `org.iso.18013.5.1` namespace. This module maps those data elements onto
+//! [`DlMandatoryElement`]s, so that an mDL wallet can compute the VCB optical
+//! data of the physical card.
+//!
+//! Values are converted to the AAMVA formats (dates, sex, height, eye color,
+//! country), but are otherwise copied verbatim: the optical data will only
+//! match if the mDL and the barcode carry the same values.
+//!
+//! See: <https://www.iso.org/standard/69084.html>
+use std::collections::BTreeMap;
+
+use super::dlid::{DlMandatoryElement, DlMandatoryElements, DlMandatoryElementsBuilder};
+use crate::optical_barcode_credential::CborValue;
+
+/// ISO/IEC 18013-5 mDL namespace.
+pub const MDL_NAMESPACE: &str = "org.iso.18013.5.1";
+
+/// mDL data element mapping error.
+#[derive(Debug, thiserror::Error)]
+pub enum MdocMappingError {
+    #[error("invalid mDL data element `{0}`")]
+    InvalidElement(&'static str),
+
+    #[error("no mDL data element maps to `{0}`")]
+    Missing(DlMandatoryElement),
+}
+
+/// Maps the `org.iso.18013.5.1` namespace data elements onto the DL mandatory
+/// elements.
+///
+/// Fails with [`MdocMappingError::Missing`] if a mandatory element cannot be
+/// derived from the namespace. See [`partial_mandatory_elements_from_mdoc_namespace`]
+/// to complete the missing elements by other means.
+pub fn mandatory_elements_from_mdoc_namespace(
+    ns: &BTreeMap<String, CborValue>,
+) -> Result<DlMandatoryElements, MdocMappingError> {
+    partial_mandatory_elements_from_mdoc_namespace(ns)?
+        .build()
+        .map_err(|e| MdocMappingError::Missing(e.0))
+}
+
+/// Maps the `org.iso.18013.5.1` namespace data elements onto the DL mandatory
+/// elements they correspond to, leaving the others unset.
+///
+/// The following elements are mapped, when present:
+///
+/// | mDL data element       | DL element |
+/// |------------------------|------------|
+/// | `document_number`      | `DAQ`      |
+/// | `family_name`          | `DCS`      |
+/// | `given_name`           | `DAC`      |
+/// | `issue_date`           | `DBD`      |
+/// | `birth_date`           | `DBB`      |
+/// | `expiry_date`          | `DBA`      |
+/// | `sex`                  | `DBC`      |
+/// | `height`               | `DAU`      |
+/// | `eye_colour`           | `DAY`      |
+/// | `resident_address`     | `DAG`      |
+/// | `resident_city`        | `DAI`      |
+/// | `resident_state`       | `DAJ`      |
+/// | `resident_postal_code` | `DAK`      |
+/// | `issuing_country`      | `DCG`      |
+///
+/// Dates are formatted as `MMDDCCYY`, or `CCYYMMDD` for Canada.
+pub fn partial_mandatory_elements_from_mdoc_namespace(
+    ns: &BTreeMap<String, CborValue>,
+) -> Result<DlMandatoryElementsBuilder, MdocMappingError> {
+    use DlMandatoryElement::*;
+    let mut result = DlMandatoryElementsBuilder::new();
+
+    let country = match text(ns, "issuing_country")? {
+        Some(alpha2) => {
+            let alpha3 = match alpha2 {
+                "US" => "USA",
+                "CA" => "CAN",
+                _ => return Err(MdocMappingError::InvalidElement("issuing_country")),
+            };
+
+            result.set(CountryIdentification, alpha3.into());
+            Some(alpha3)
+        }
+        None => None,
+    };
+
+    for (name, element) in [
+        ("document_number", CustomerIdNumber),
+        ("family_name", CustomerFamilyName),
+        ("given_name", CustomerFirstName),
+        ("resident_address", AddressStreet1),
+        ("resident_city", AddressCity),
+        ("resident_state", AddressJurisdictionCode),
+    ] {
+        if let Some(value) = text(ns, name)? {
+            result.set(element, value.into());
+        }
+    }
+
+    if let Some(value) = text(ns, "resident_postal_code")? {
+        result.set(AddressPostalCode, format!("{value:<11}").into());
+    }
+
+    for (name, element) in [
+        ("issue_date", DocumentIssueDate),
+        ("birth_date", DateOfBirth),
+        ("expiry_date", DocumentExpirationDate),
+    ] {
+        if let Some(value) = date(ns, name, country == Some("CAN"))? {
+            result.set(element, value.into());
+        }
+    }
+
+    if let Some(sex) = uint(ns, "sex")? {
+        // ISO/IEC 5218 codes match the AAMVA ones.
+        match sex {
+            1 | 2 | 9 => result.set(Sex, sex.to_string().into()),
+            _ => return Err(MdocMappingError::InvalidElement("sex")),
+        }
+    }
+
+    if let Some(height) = uint(ns, "height")? {
+        if height > 999 {
+            return Err(MdocMappingError::InvalidElement("height"));
+        }
+
+        result.set(Height, format!("{height:03} cm").into());
+    }
+
+    if let Some(color) = text(ns, "eye_colour")? {
+        let code = match color {
+            "black" => "BLK",
+            "blue" => "BLU",
+            "brown" => "BRO",
+            "dichromatic" => "DIC",
+            "grey" => "GRY",
+            "green" => "GRN",
+            "hazel" => "HAZ",
+            "maroon" => "MAR",
+            "pink" => "PNK",
+            "unknown" => "UNK",
+            _ => return Err(MdocMappingError::InvalidElement("eye_colour")),
+        };
+
+        result.set(EyeColor, code.into());
+    }
+
+    Ok(result)
+}
+
+fn text<'a>(
+    ns: &'a BTreeMap<String, CborValue>,
+    name: &'static str,
+) -> Result<Option<&'a str>, MdocMappingError> {
+    match ns.get(name) {
+        Some(CborValue::Text(value)) => Ok(Some(value)),
+        Some(_) => Err(MdocMappingError::InvalidElement(name)),
+        None => Ok(None),
+    }
+}
+
+fn uint(
+    ns: &BTreeMap<String, CborValue>,
+    name: &'static str,
+) -> Result<Option<u64>, MdocMappingError> {
+    match ns.get(name) {
+        Some(CborValue::Integer(value)) => u64::try_from(*value)
+            .map(Some)
+            .map_err(|_| MdocMappingError::InvalidElement(name)),
+        Some(_) => Err(MdocMappingError::InvalidElement(name)),
+        None => Ok(None),
+    }
+}
+
+/// Reads a `full-date` (`YYYY-MM-DD`, tag 1004) or `tdate` (tag 0) element,
+/// formatted as an AAMVA date.
+fn date(
+    ns: &BTreeMap<String, CborValue>,
+    name: &'static str,
+    canada: bool,
+) -> Result<Option<String>, MdocMappingError> {
+    let value = match ns.get(name) {
+        Some(CborValue::Tag(_, value)) => value.as_ref(),
+        Some(value) => value,
+        None => return Ok(None),
+    };
+
+    let date = value
+        .as_text()
+        .and_then(|s| s.get(..10))
+        .filter(|s| {
+            s.bytes().enumerate().all(|(i, b)| match i {
+                4 | 7 => b == b'-',
+                _ => b.is_ascii_digit(),
+            })
+        })
+        .ok_or(MdocMappingError::InvalidElement(name))?;
+
+    let (year, month, day) = (&date[..4], &date[5..7], &date[8..10]);
+    if canada {
+        Ok(Some(format!("{year}{month}{day}")))
+    } else {
+        Ok(Some(format!("{month}{day}{year}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{aamva::dlid::DlMandatoryElement, optical_barcode_credential::CborValue};
+
+    use super::{
+        mandatory_elements_from_mdoc_namespace, partial_mandatory_elements_from_mdoc_namespace,
+        MdocMappingError,
+    };
+
+    fn namespace() -> BTreeMap<String, CborValue> {
+        let full_date = |s: &str| CborValue::Tag(1004, Box::new(CborValue::Text(s.to_owned())));
+
+        [
+            ("document_number", CborValue::Text("F987654321".into())),
+            ("family_name", CborValue::Text("SMITH".into())),
+            ("given_name", CborValue::Text("JOHN".into())),
+            ("birth_date", full_date("1988-04-19")),
+            ("issue_date", full_date("2024-01-01")),
+            ("expiry_date", full_date("2030-04-19")),
+            ("issuing_country", CborValue::Text("US".into())),
+            ("sex", CborValue::Integer(1.into())),
+            ("height", CborValue::Integer(175.into())),
+            ("eye_colour", CborValue::Text("brown".into())),
+            ("resident_postal_code", CborValue::Text("F87P20000".into())),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_owned(), v))
+        .collect()
+    }
+
+    #[test]
+    fn map_mdoc_namespace() {
+        let partial = partial_mandatory_elements_from_mdoc_namespace(&namespace()).unwrap();
+        let get = |e| partial.get(e).map(|v| std::str::from_utf8(v).unwrap());
+        assert_eq!(get(DlMandatoryElement::CustomerFirstName), Some("JOHN"));
+        assert_eq!(get(DlMandatoryElement::DateOfBirth), Some("04191988"));
+        assert_eq!(get(DlMandatoryElement::CountryIdentification), Some("USA"));
+        assert_eq!(get(DlMandatoryElement::Sex), Some("1"));
+        assert_eq!(get(DlMandatoryElement::Height), Some("175 cm"));
+        assert_eq!(get(DlMandatoryElement::EyeColor), Some("BRO"));
+        assert_eq!(
+            get(DlMandatoryElement::AddressPostalCode),
+            Some("F87P20000  ")
+        );
+        assert_eq!(get(DlMandatoryElement::VehicleClass), None);
+
+        assert!(matches!(
+            mandatory_elements_from_mdoc_namespace(&namespace()),
+            Err(MdocMappingError::Missing(_))
+        ));
+
+        let mut invalid = namespace();
+        invalid.insert("sex".to_owned(), CborValue::Text("M".into()));
+        assert!(matches!(
+            partial_mandatory_elements_from_mdoc_namespace(&invalid),
+            Err(MdocMappingError::InvalidElement("sex"))
+        ))
+    }
+}
@@ -1,23 +1,42 @@
+#[cfg(feature = "std")]
 use dlid::{
     pdf_417::{read_array, RecordEntry},
-    DlMandatoryElement, DlMandatoryElements,
+    DlElement, DlMandatoryElement, DlMandatoryElements, DlSubfile,
 };
+#[cfg(feature = "std")]
 use lazy_static::lazy_static;
+#[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "std")]
 use sha2::{Digest, Sha256};
-use ssi::security::{
-    multibase::{self, Base},
-    Multibase, MultibaseBuf,
+#[cfg(feature = "std")]
+use ssi::{
+    claims::{ProofValidationError, Verification},
+    security::{
+        multibase::{self, Base},
+        Multibase, MultibaseBuf,
+    },
+    verification_methods::{Multikey, VerificationMethodResolver},
 };
-use std::{collections::HashMap, io};
+#[cfg(feature = "std")]
+use std::{collections::HashMap, fmt, io, str::FromStr};
 
 pub mod dlid;
-
-use crate::optical_barcode_credential::{
-    decode_from_bytes, encode_to_bytes, DecodeError, OpticalBarcodeCredentialSubject,
-    VerifiableOpticalBarcodeCredential,
+pub mod iin;
+#[cfg(feature = "std")]
+pub mod mdoc;
+
+#[cfg(feature = "std")]
+use crate::{
+    optical_barcode_credential::{
+        decode_any_from_bytes, decode_from_bytes, encode_to_bytes, verify, DecodeError, DecodedVcb,
+        OpticalBarcodeCredentialSubject, VerifiableOpticalBarcodeCredential,
+        VerificationParameters,
+    },
+    terse_bitstring_status_list_entry::TerseStatusListProvider,
 };
 
+#[cfg(feature = "std")]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub struct AamvaDriversLicenseScannableInformation {
@@ -26,20 +45,81 @@ pub struct AamvaDriversLicenseScannableInformation {
     protected_component_index: EncodedProtectedComponentIndex,
 }
 
+#[cfg(feature = "std")]
+impl AamvaDriversLicenseScannableInformation {
+    /// Returns the elements present in the given subfile that are not
+    /// protected by the signature.
+    ///
+    /// Optional elements are never protected. If the protected component
+    /// index is invalid, every present element is returned.
+    pub fn unprotected_fields(&self, subfile: &DlSubfile) -> Vec<DlElement> {
+        let index = self.protected_component_index.decode().unwrap_or_default();
+        subfile
+            .iter()
+            .filter_map(|(e, _)| match e {
+                DlElement::Mandatory(m) if index.contains(m) => None,
+                e => Some(e),
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "std")]
+impl DlSubfile {
+    /// Checks that this subfile matches the given credential.
+    ///
+    /// The optical data is recomputed from this subfile according to the
+    /// credential protected component index, and the credential proof is
+    /// verified against it: the proof is invalid if a protected field was
+    /// altered after signing. Fails if the index is invalid, or references
+    /// elements that do not exist.
+    pub async fn validate_against_index<R, C>(
+        &self,
+        vc: &VerifiableOpticalBarcodeCredential<AamvaDriversLicenseScannableInformation>,
+        params: VerificationParameters<R, C>,
+    ) -> Result<Verification, ProofValidationError>
+    where
+        R: VerificationMethodResolver<Method = Multikey>,
+        C: TerseStatusListProvider,
+    {
+        for subject in vc.credential_subjects.iter() {
+            match subject.protected_component_index.decode() {
+                Ok(index) if index.has_known_elements_only() => (),
+                _ => {
+                    return Err(ProofValidationError::other(
+                        "invalid protected component index",
+                    ))
+                }
+            }
+        }
+
+        verify(vc, &self.mandatory, params).await
+    }
+}
+
+#[cfg(feature = "std")]
 unsafe impl OpticalBarcodeCredentialSubject for AamvaDriversLicenseScannableInformation {
-    // type Context = CitizenshipV2;
     type ExtraInformation = DlMandatoryElements;
 
-    fn create_optical_data(&self, xi: &Self::ExtraInformation) -> [u8; 32] {
+    const TYPE: &'static str = "AamvaDriversLicenseScannableInformation";
+
+    fn create_canonical_optical_data(&self, xi: &Self::ExtraInformation) -> Vec<u8> {
         let index = self.protected_component_index.decode().unwrap();
-        index.to_optical_data_bytes(xi)
+        index.to_canonical_optical_data(xi)
+    }
+
+    fn protected_elements(&self) -> Option<Vec<DlMandatoryElement>> {
+        let index = self.protected_component_index.decode().ok()?;
+        Some(index.iter().collect())
     }
 }
 
+#[cfg(feature = "std")]
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct EncodedProtectedComponentIndex(pub MultibaseBuf);
 
+#[cfg(feature = "std")]
 impl EncodedProtectedComponentIndex {
     pub fn encode(index: &ProtectedComponentIndex) -> Self {
         Self(index.encode())
@@ -50,44 +130,114 @@ impl EncodedProtectedComponentIndex {
     }
 }
 
-#[derive(Debug, Default, Serialize, Deserialize)]
-#[serde(transparent)]
+/// Protected component index.
+///
+/// Serialized as its multibase-encoded three-byte form (e.g. `"uggAg"`), same
+/// as [`EncodedProtectedComponentIndex`].
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
 pub struct ProtectedComponentIndex(u32);
 
+#[cfg(feature = "std")]
+impl Serialize for ProtectedComponentIndex {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.encode().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'de> Deserialize<'de> for ProtectedComponentIndex {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let multibase = MultibaseBuf::deserialize(deserializer)?;
+        Self::decode(&multibase).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "std")]
 impl ProtectedComponentIndex {
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// Creates an index protecting the given elements.
+    ///
+    /// Useful to compute the optical data of an arbitrary selection of
+    /// elements with [`Self::to_optical_data_bytes`].
+    pub fn from_elements(elements: impl IntoIterator<Item = DlMandatoryElement>) -> Self {
+        let mut result = Self::new();
+        result.extend(elements);
+        result
+    }
+
     pub fn decode(multibase: &Multibase) -> Result<Self, InvalidProtectedComponentIndex> {
         let (_, bytes) = multibase.decode()?;
         match <[u8; 3]>::try_from(bytes) {
-            Ok(b) => Ok(Self(u32::from_be_bytes([0, b[0], b[1], b[2]]))),
+            Ok(b) => Ok(Self::from_be_bytes(b)),
             Err(_) => Err(InvalidProtectedComponentIndex::Invalid),
         }
     }
 
     pub fn encode(&self) -> MultibaseBuf {
-        let bytes = self.0.to_be_bytes();
-        MultibaseBuf::encode(multibase::Base::Base64Url, &bytes[1..])
+        MultibaseBuf::encode(multibase::Base::Base64Url, &self.to_be_bytes())
+    }
+
+    /// Creates an index from its raw 24-bit big-endian representation, as
+    /// encoded in the multibase string.
+    pub fn from_be_bytes(bytes: [u8; 3]) -> Self {
+        Self(u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]))
+    }
+
+    /// Returns the raw 24-bit big-endian representation of this index.
+    pub fn to_be_bytes(&self) -> [u8; 3] {
+        let [_, a, b, c] = self.0.to_be_bytes();
+        [a, b, c]
     }
 
     pub fn into_u32(&self) -> u32 {
         self.0
     }
 
+    /// Returns the canonical list of protected elements, sorted by
+    /// identifier.
+    ///
+    /// The element at index `i` is represented by the bit at position `i` of
+    /// the 24-bit index, counting from the most significant bit.
+    pub fn canonical_elements() -> &'static [DlMandatoryElement; 22] {
+        &PROTECTED_COMPONENTS_LIST
+    }
+
+    /// Returns the position (0 to 23, counting from the most significant
+    /// bit) of the bit representing the given element in the 24-bit index.
+    pub fn bit_of(e: DlMandatoryElement) -> u8 {
+        *PROTECTED_COMPONENTS_INDEXES.get(&e).unwrap() as u8
+    }
+
     fn mask_of_index(i: usize) -> u32 {
         1u32 << (23 - i)
     }
 
     fn mask_of(e: DlMandatoryElement) -> u32 {
-        Self::mask_of_index(*PROTECTED_COMPONENTS_INDEXES.get(&e).unwrap())
+        Self::mask_of_index(Self::bit_of(e) as usize)
     }
 
     fn contains_index(&self, i: usize) -> bool {
         self.0 & Self::mask_of_index(i) != 0
     }
 
+    /// Checks that no bit is set outside of the
+    /// [canonical elements](Self::canonical_elements).
+    pub fn has_known_elements_only(&self) -> bool {
+        let known =
+            (0..Self::canonical_elements().len()).fold(0, |mask, i| mask | Self::mask_of_index(i));
+        self.0 & !known == 0
+    }
+
     pub fn contains(&self, e: DlMandatoryElement) -> bool {
         self.0 & Self::mask_of(e) != 0
     }
@@ -113,12 +263,21 @@ impl ProtectedComponentIndex {
             })
     }
 
-    pub fn to_optical_data_bytes(&self, elements: &DlMandatoryElements) -> [u8; 32] {
-        let mut data_to_canonicalize = Vec::new();
+    /// Iterates over the protected elements along with their value in the
+    /// given `elements`.
+    pub fn iter_with<'a>(
+        &'a self,
+        elements: &'a DlMandatoryElements,
+    ) -> impl 'a + Iterator<Item = (DlMandatoryElement, &'a [u8])> {
+        self.iter().map(move |e| (e, elements.get(e)))
+    }
 
-        for field in self.iter() {
-            let data = elements.get(field);
+    /// Returns the canonical bytes hashed by
+    /// [`to_optical_data_bytes`](Self::to_optical_data_bytes).
+    pub fn to_canonical_optical_data(&self, elements: &DlMandatoryElements) -> Vec<u8> {
+        let mut data_to_canonicalize = Vec::new();
 
+        for (field, data) in self.iter_with(elements) {
             let mut entry = Vec::with_capacity(3 + data.len() + 1);
             entry.extend(field.id());
             entry.extend(data);
@@ -128,20 +287,158 @@ impl ProtectedComponentIndex {
         }
 
         data_to_canonicalize.sort_unstable();
-        let canonical_data = data_to_canonicalize.as_slice().join([].as_slice());
-        Sha256::digest(canonical_data).into()
+        data_to_canonicalize.as_slice().join([].as_slice())
+    }
+
+    pub fn to_optical_data_bytes(&self, elements: &DlMandatoryElements) -> [u8; 32] {
+        Sha256::digest(self.to_canonical_optical_data(elements)).into()
+    }
+
+    /// Reports which of the given mandatory elements are covered by this
+    /// index, along with the resulting optical data.
+    pub fn coverage_report(&self, elements: &DlMandatoryElements) -> CoverageReport {
+        let mut signed = Vec::new();
+        let mut unsigned = Vec::new();
+
+        for &e in Self::canonical_elements() {
+            if self.contains(e) {
+                signed.push(e)
+            } else if !elements.get(e).is_empty() {
+                unsigned.push(e)
+            }
+        }
+
+        CoverageReport {
+            signed,
+            unsigned,
+            optical_data: self.to_optical_data_bytes(elements),
+        }
     }
 }
 
+/// Protected component coverage report.
+///
+/// See [`ProtectedComponentIndex::coverage_report`].
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoverageReport {
+    /// Elements protected by the signature.
+    pub signed: Vec<DlMandatoryElement>,
+
+    /// Elements present in the subfile, but not protected by the signature.
+    pub unsigned: Vec<DlMandatoryElement>,
+
+    /// Optical data digest.
+    pub optical_data: [u8; 32],
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for CoverageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for e in &self.signed {
+            writeln!(f, "signed    {}", e.string_id())?;
+        }
+
+        for e in &self.unsigned {
+            writeln!(f, "unsigned  {}", e.string_id())?;
+        }
+
+        write!(f, "optical data: ")?;
+        for b in self.optical_data {
+            write!(f, "{b:02x}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Displays the protected elements as their pipe-separated identifiers, for
+/// instance `DAC|DAQ|DCS`.
+#[cfg(feature = "std")]
+impl fmt::Display for ProtectedComponentIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, e) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str("|")?;
+            }
+
+            write!(f, "{e}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses pipe-separated element identifiers, as displayed by
+/// [`ProtectedComponentIndex`]'s `Display` implementation.
+#[cfg(feature = "std")]
+impl FromStr for ProtectedComponentIndex {
+    type Err = InvalidProtectedComponentIndex;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut result = Self::new();
+
+        if !s.is_empty() {
+            for id in s.split('|') {
+                let e = DlMandatoryElement::from_code(id)
+                    .ok_or_else(|| InvalidProtectedComponentIndex::UnknownElement(id.to_owned()))?;
+                result.insert(e);
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Extend<DlMandatoryElement> for ProtectedComponentIndex {
+    fn extend<T: IntoIterator<Item = DlMandatoryElement>>(&mut self, iter: T) {
+        for e in iter {
+            self.insert(e)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl FromIterator<DlMandatoryElement> for ProtectedComponentIndex {
+    fn from_iter<T: IntoIterator<Item = DlMandatoryElement>>(iter: T) -> Self {
+        Self::from_elements(iter)
+    }
+}
+
+#[cfg(feature = "std")]
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum InvalidProtectedComponentIndex {
     #[error(transparent)]
     Multibase(#[from] multibase::Error),
 
     #[error("invalid component index set")]
     Invalid,
+
+    #[error("unknown data element `{0}`")]
+    UnknownElement(String),
+}
+
+#[cfg(feature = "std")]
+impl InvalidProtectedComponentIndex {
+    /// Checks if this is a [`Self::Multibase`] error.
+    pub fn is_multibase(&self) -> bool {
+        matches!(self, Self::Multibase(_))
+    }
+
+    /// Checks if this is a [`Self::Invalid`] error.
+    pub fn is_invalid(&self) -> bool {
+        matches!(self, Self::Invalid)
+    }
+
+    /// Checks if this is a [`Self::UnknownElement`] error.
+    pub fn is_unknown_element(&self) -> bool {
+        matches!(self, Self::UnknownElement(_))
+    }
 }
 
+#[cfg(feature = "std")]
 lazy_static! {
     pub static ref PROTECTED_COMPONENTS_LIST: [DlMandatoryElement; 22] = {
         let mut list = DlMandatoryElement::LIST;
@@ -159,13 +456,17 @@ lazy_static! {
     };
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use lazy_static::lazy_static;
 
     use crate::aamva::dlid::DlMandatoryElement;
 
-    use super::{dlid::DlSubfile, ProtectedComponentIndex};
+    use super::{
+        dlid::{DlElement, DlSubfile},
+        AamvaDriversLicenseScannableInformation, EncodedProtectedComponentIndex,
+        ProtectedComponentIndex,
+    };
 
     const DL_SUBFILE_BYTES: &str = "DLDACJOHN\nDADNONE\nDAG123 MAIN ST\nDAIANYVILLE\nDAJUTO\nDAKF87P20000\nDAQF987654321\nDAU069 IN\nDAYBRO\nDBA04192030\nDBB04191988\nDBC1\nDBD01012024\nDCAC\nDCBNONE\nDCDNONE\nDCFUTODOCDISCRIM\nDCGUTO\nDCSSMITH\nDDEN\nDDFN\nDDGN\r";
 
@@ -190,9 +491,62 @@ mod tests {
         index.insert(DlMandatoryElement::CustomerIdNumber);
         assert_eq!(index.into_u32(), 0b100000100000000000100000);
 
+        let canonical = index.to_canonical_optical_data(&DL_SUBFILE.mandatory);
+        assert_eq!(canonical, b"DACJOHN\nDAQF987654321\nDCSSMITH\n");
+
         let bytes = index.to_optical_data_bytes(&DL_SUBFILE.mandatory);
 
-        assert_eq!(bytes, expected)
+        assert_eq!(bytes, expected);
+
+        let report = index.coverage_report(&DL_SUBFILE.mandatory);
+        assert_eq!(
+            report.signed,
+            [
+                DlMandatoryElement::CustomerFirstName,
+                DlMandatoryElement::CustomerIdNumber,
+                DlMandatoryElement::CustomerFamilyName
+            ]
+        );
+        assert_eq!(report.unsigned.len(), 19);
+        assert_eq!(report.optical_data, expected)
+    }
+
+    #[test]
+    fn protected_component_index_from_elements() {
+        let index = ProtectedComponentIndex::from_elements([
+            DlMandatoryElement::CustomerFirstName,
+            DlMandatoryElement::CustomerFamilyName,
+            DlMandatoryElement::CustomerIdNumber,
+        ]);
+        assert_eq!(index.into_u32(), 0b100000100000000000100000);
+
+        let what_if: ProtectedComponentIndex = [
+            DlMandatoryElement::CustomerFirstName,
+            DlMandatoryElement::DateOfBirth,
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            what_if.to_canonical_optical_data(&DL_SUBFILE.mandatory),
+            b"DACJOHN\nDBB04191988\n"
+        )
+    }
+
+    #[test]
+    fn unprotected_fields() {
+        let mut index = ProtectedComponentIndex::new();
+        index.insert(DlMandatoryElement::CustomerFirstName);
+        index.insert(DlMandatoryElement::CustomerFamilyName);
+        index.insert(DlMandatoryElement::CustomerIdNumber);
+
+        let subject = AamvaDriversLicenseScannableInformation {
+            protected_component_index: EncodedProtectedComponentIndex::encode(&index),
+        };
+
+        let unprotected = subject.unprotected_fields(&DL_SUBFILE);
+        assert_eq!(unprotected.len(), DL_SUBFILE.len() - 3);
+        assert!(!unprotected.contains(&DlElement::Mandatory(DlMandatoryElement::CustomerFirstName)));
+        assert!(unprotected.contains(&DlElement::Mandatory(DlMandatoryElement::DateOfBirth)))
     }
 
     #[test]
@@ -205,12 +559,87 @@ mod tests {
 
         assert_eq!(encoded.as_str(), "uggAg")
     }
+
+    #[test]
+    fn serialize_protected_component_index() {
+        let mut index = ProtectedComponentIndex::new();
+        index.insert(DlMandatoryElement::CustomerFirstName);
+        index.insert(DlMandatoryElement::CustomerFamilyName);
+        index.insert(DlMandatoryElement::CustomerIdNumber);
+
+        let json = json_syntax::to_value(&index).unwrap();
+        let encoded =
+            json_syntax::to_value(EncodedProtectedComponentIndex::encode(&index)).unwrap();
+        assert_eq!(json.as_str(), Some("uggAg"));
+        assert_eq!(json, encoded);
+
+        let decoded: ProtectedComponentIndex = json_syntax::from_value(json).unwrap();
+        assert_eq!(decoded.into_u32(), index.into_u32())
+    }
+
+    #[test]
+    fn display_protected_component_index() {
+        let mut index = ProtectedComponentIndex::new();
+        index.insert(DlMandatoryElement::CustomerFirstName);
+        index.insert(DlMandatoryElement::CustomerFamilyName);
+        index.insert(DlMandatoryElement::CustomerIdNumber);
+        assert_eq!(index.to_string(), "DAC|DAQ|DCS");
+
+        let parsed: ProtectedComponentIndex = "DCS|DAC|DAQ".parse().unwrap();
+        assert_eq!(parsed.into_u32(), index.into_u32());
+        assert!("DAC|XYZ".parse::<ProtectedComponentIndex>().is_err());
+        assert_eq!("".parse::<ProtectedComponentIndex>().unwrap().into_u32(), 0)
+    }
+
+    #[test]
+    fn protected_component_bits() {
+        let elements = ProtectedComponentIndex::canonical_elements();
+        assert!(elements.windows(2).all(|w| w[0].id() < w[1].id()));
+
+        for (i, e) in elements.iter().enumerate() {
+            assert_eq!(ProtectedComponentIndex::bit_of(*e) as usize, i);
+
+            let mut index = ProtectedComponentIndex::new();
+            index.insert(*e);
+            assert_eq!(index.into_u32(), 1 << (23 - i))
+        }
+
+        assert_eq!(
+            ProtectedComponentIndex::bit_of(DlMandatoryElement::CustomerFirstName),
+            0
+        )
+    }
+
+    #[test]
+    fn protected_component_index_be_bytes() {
+        let index = ProtectedComponentIndex::from_elements([
+            DlMandatoryElement::CustomerFirstName,
+            DlMandatoryElement::CustomerFamilyName,
+            DlMandatoryElement::CustomerIdNumber,
+        ]);
+        assert_eq!(index.to_be_bytes(), [0x82, 0x00, 0x20]);
+        assert_eq!(index.encode().as_str(), "uggAg");
+
+        let parsed = ProtectedComponentIndex::from_be_bytes([0x82, 0x00, 0x20]);
+        assert_eq!(parsed.into_u32(), index.into_u32())
+    }
 }
 
+/// VC Barcodes subfile (`ZZ`), holding the base64url encoded credential in
+/// its `ZZA` element.
+///
+/// The AAMVA standard reserves subfile types starting with `Z` for
+/// jurisdiction-specific data, a jurisdiction usually using `Z` followed by
+/// the first letter of its name (e.g. `ZV` for Virginia). VC Barcodes use
+/// `ZZ`, which jurisdictions should therefore not use for their own data. See
+/// [`CredentialsSubfile`] to store more than one credential.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
 pub struct ZZSubfile {
     pub zza: String,
 }
 
+#[cfg(feature = "std")]
 impl ZZSubfile {
     pub async fn encode_credential(
         vc: &VerifiableOpticalBarcodeCredential<AamvaDriversLicenseScannableInformation>,
@@ -226,22 +655,83 @@ impl ZZSubfile {
         VerifiableOpticalBarcodeCredential<AamvaDriversLicenseScannableInformation>,
         ZZDecodeError,
     > {
-        let bytes = Base::Base64UrlPad.decode(&self.zza)?;
+        let bytes = decode_zz_payload(&self.zza)?;
         decode_from_bytes::<AamvaDriversLicenseScannableInformation>(&bytes)
             .await
             .map_err(Into::into)
     }
 }
 
+/// Decodes a base64url `ZZ` element value.
+///
+/// Credentials are encoded with padding, but some implementations omit it:
+/// unpadded values are accepted too.
+#[cfg(feature = "std")]
+pub(crate) fn decode_zz_payload(value: &str) -> Result<Vec<u8>, ZZDecodeError> {
+    if value.is_empty() {
+        return Err(ZZDecodeError::Empty);
+    }
+
+    Base::Base64UrlPad
+        .decode(value)
+        .or_else(|_| Base::Base64Url.decode(value))
+        .map_err(|_| ZZDecodeError::InvalidBase64 {
+            position: invalid_base64_url_position(value),
+        })
+}
+
+/// Returns the position of the first invalid character in the given padded
+/// base64url string, or its length if it is truncated.
+#[cfg(feature = "std")]
+fn invalid_base64_url_position(value: &str) -> usize {
+    let bytes = value.as_bytes();
+    let data_len = bytes.iter().position(|&b| b == b'=').unwrap_or(bytes.len());
+
+    bytes[..data_len]
+        .iter()
+        .position(|&b| !(b.is_ascii_alphanumeric() || b == b'-' || b == b'_'))
+        .or_else(|| {
+            bytes[data_len..]
+                .iter()
+                .position(|&b| b != b'=')
+                .map(|i| data_len + i)
+        })
+        .unwrap_or(bytes.len())
+}
+
+#[cfg(feature = "std")]
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum ZZDecodeError {
-    #[error(transparent)]
-    Base64(#[from] multibase::Error),
+    #[error("empty `ZZA` payload")]
+    Empty,
+
+    #[error("invalid base64url `ZZA` payload at position {position}")]
+    InvalidBase64 { position: usize },
 
     #[error(transparent)]
     CborLd(#[from] DecodeError),
 }
 
+#[cfg(feature = "std")]
+impl ZZDecodeError {
+    /// Checks if this is a [`Self::Empty`] error.
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Self::Empty)
+    }
+
+    /// Checks if this is a [`Self::InvalidBase64`] error.
+    pub fn is_invalid_base64(&self) -> bool {
+        matches!(self, Self::InvalidBase64 { .. })
+    }
+
+    /// Checks if this is a [`Self::CborLd`] error.
+    pub fn is_cbor_ld(&self) -> bool {
+        matches!(self, Self::CborLd(_))
+    }
+}
+
+#[cfg(feature = "std")]
 impl dlid::pdf_417::DecodeSubfile for ZZSubfile {
     fn decode_subfile(reader: &mut impl io::BufRead) -> io::Result<Self> {
         if read_array(reader)? != *b"ZZ" {
@@ -260,6 +750,16 @@ impl dlid::pdf_417::DecodeSubfile for ZZSubfile {
     }
 }
 
+#[cfg(feature = "std")]
+impl TryFrom<&dlid::pdf_417::Subfile> for ZZSubfile {
+    type Error = io::Error;
+
+    fn try_from(value: &dlid::pdf_417::Subfile) -> io::Result<Self> {
+        value.decode()
+    }
+}
+
+#[cfg(feature = "std")]
 impl From<ZZSubfile> for dlid::pdf_417::Subfile {
     fn from(value: ZZSubfile) -> Self {
         let mut data = Vec::new();
@@ -268,3 +768,105 @@ impl From<ZZSubfile> for dlid::pdf_417::Subfile {
         Self::new(*b"ZZ", data)
     }
 }
+
+/// VC Barcodes subfile (`ZZ`) holding several credentials.
+///
+/// Credentials are base64url encoded in the `ZZA`, `ZZB`, ..., `ZZZ`
+/// elements, in order, for instance to carry both an AAMVA and an MRZ
+/// credential. A subfile with a single credential is identical to a
+/// [`ZZSubfile`].
+#[cfg(feature = "std")]
+#[derive(Debug, Default, Clone)]
+pub struct CredentialsSubfile {
+    pub credentials: Vec<String>,
+}
+
+#[cfg(feature = "std")]
+impl CredentialsSubfile {
+    /// Maximum number of credentials, one per `ZZA`-`ZZZ` element.
+    pub const MAX_LEN: usize = 26;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Encodes and appends the given credential.
+    pub async fn push_credential<T: OpticalBarcodeCredentialSubject>(
+        &mut self,
+        vc: &VerifiableOpticalBarcodeCredential<T>,
+    ) {
+        self.credentials
+            .push(Base::Base64UrlPad.encode(encode_to_bytes(vc).await))
+    }
+
+    /// Decodes every credential, whatever its credential subject type.
+    pub async fn decode_credentials(&self) -> Result<Vec<DecodedVcb>, ZZDecodeError> {
+        let mut result = Vec::with_capacity(self.credentials.len());
+
+        for credential in &self.credentials {
+            let bytes = decode_zz_payload(credential)?;
+            result.push(decode_any_from_bytes(&bytes).await?);
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<ZZSubfile> for CredentialsSubfile {
+    fn from(value: ZZSubfile) -> Self {
+        Self {
+            credentials: vec![value.zza],
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl dlid::pdf_417::DecodeSubfile for CredentialsSubfile {
+    fn decode_subfile(reader: &mut impl io::BufRead) -> io::Result<Self> {
+        if read_array(reader)? != *b"ZZ" {
+            return Err(io::ErrorKind::InvalidData.into());
+        }
+
+        let mut credentials = Vec::new();
+
+        loop {
+            let (entry, last) = RecordEntry::decode(reader)?;
+
+            if credentials.len() == Self::MAX_LEN
+                || entry.field != [b'Z', b'Z', b'A' + credentials.len() as u8]
+            {
+                return Err(io::ErrorKind::InvalidData.into());
+            }
+
+            credentials
+                .push(String::from_utf8(entry.value).map_err(|_| io::ErrorKind::InvalidData)?);
+
+            if last {
+                break Ok(Self { credentials });
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryFrom<CredentialsSubfile> for dlid::pdf_417::Subfile {
+    type Error = io::Error;
+
+    /// Fails if there are no credentials, or more than
+    /// [`CredentialsSubfile::MAX_LEN`].
+    fn try_from(value: CredentialsSubfile) -> io::Result<Self> {
+        if value.credentials.is_empty() || value.credentials.len() > CredentialsSubfile::MAX_LEN {
+            return Err(io::ErrorKind::InvalidInput.into());
+        }
+
+        let last = value.credentials.len() - 1;
+        let mut data = Vec::new();
+        for (i, credential) in value.credentials.iter().enumerate() {
+            let field = [b'Z', b'Z', b'A' + i as u8];
+            RecordEntry::encode_ref(&mut data, &field, credential.as_bytes(), i == last)?;
+        }
+
+        Ok(Self::new(*b"ZZ", data))
+    }
+}
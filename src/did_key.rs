@@ -0,0 +1,74 @@
+//! Offline `did:key` verification method resolution.
+//!
+//! VCB issuers commonly use `did:key` verification methods, whose public key
+//! is embedded in the identifier itself. [`DidKeyResolver`] resolves them
+//! without any network access or DID document processing, which is enough for
+//! embedded verifiers that never see other DID methods.
+//!
+//! See: <https://w3c-ccg.github.io/did-method-key/>
+use std::borrow::Cow;
+
+use iref::{Iri, UriBuf};
+use ssi::{
+    security::MultibaseBuf,
+    verification_methods::{
+        multikey::PublicKey, Multikey, ReferenceOrOwnedRef, ResolutionOptions,
+        VerificationMethodResolutionError, VerificationMethodResolver,
+    },
+};
+
+use crate::ecdsa_xi_2023::Curve;
+
+/// Offline `did:key` verification method resolver.
+///
+/// Only resolves `did:key:{key}#{key}` verification methods, where `{key}` is
+/// a base58-btc (`z`) multibase-encoded P-256 or P-384 multikey, the curves
+/// supported by `ecdsa-xi-2023`. Any other identifier fails with
+/// [`VerificationMethodResolutionError::InvalidKeyId`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DidKeyResolver;
+
+impl DidKeyResolver {
+    /// Resolves the given `did:key` verification method.
+    pub fn resolve(id: &Iri) -> Result<Multikey, VerificationMethodResolutionError> {
+        let invalid = || VerificationMethodResolutionError::InvalidKeyId(id.to_string());
+
+        let (key, fragment) = id
+            .as_str()
+            .strip_prefix("did:key:")
+            .and_then(|suffix| suffix.split_once('#'))
+            .ok_or_else(invalid)?;
+
+        if key != fragment || !key.starts_with('z') {
+            return Err(invalid());
+        }
+
+        let encoded = MultibaseBuf::new(key.to_owned());
+        let (_, bytes) = encoded.decode().map_err(|_| invalid())?;
+        Curve::from_multicodec(&bytes).map_err(|_| invalid())?;
+
+        Ok(Multikey {
+            id: id.to_owned(),
+            controller: UriBuf::new(format!("did:key:{key}").into_bytes())
+                .map_err(|_| invalid())?,
+            public_key: PublicKey { encoded },
+        })
+    }
+}
+
+impl VerificationMethodResolver for DidKeyResolver {
+    type Method = Multikey;
+
+    async fn resolve_verification_method_with(
+        &self,
+        _issuer: Option<&Iri>,
+        method: Option<ReferenceOrOwnedRef<'_, Multikey>>,
+        _options: ResolutionOptions,
+    ) -> Result<Cow<Multikey>, VerificationMethodResolutionError> {
+        match method {
+            Some(ReferenceOrOwnedRef::Owned(method)) => Ok(Cow::Borrowed(method)),
+            Some(ReferenceOrOwnedRef::Reference(id)) => Self::resolve(id).map(Cow::Owned),
+            None => Err(VerificationMethodResolutionError::MissingVerificationMethod),
+        }
+    }
+}
@@ -1,3 +1,6 @@
+use std::fmt;
+
+use iref::IriBuf;
 use sha2::{Digest, Sha256, Sha384};
 use ssi::{
     claims::{
@@ -20,9 +23,11 @@ use ssi::{
     crypto::algorithm::ES256OrES384,
     json_ld::{Expandable, JsonLdNodeObject},
     rdf::{AnyLdEnvironment, LdEnvironment},
-    verification_methods::{multikey, Multikey},
+    verification_methods::Multikey,
 };
 
+use crate::{DateTime, Utc};
+
 /// The `ecdsa-xi-2023` cryptosuite.
 ///
 /// See: <https://w3c-ccg.github.io/vc-barcodes/#ecdsa-xi-2023>
@@ -58,9 +63,138 @@ impl StandardCryptographicSuite for EcdsaXi2023 {
     }
 }
 
+impl EcdsaXi2023 {
+    /// Checks that the given verification method can be used with this
+    /// cryptosuite.
+    ///
+    /// As `ecdsa-rdfc-2019` on which it is based, `ecdsa-xi-2023` only
+    /// supports P-256 and P-384 keys. In particular, secp256k1 keys are not
+    /// allowed.
+    pub fn check_verification_method(method: &Multikey) -> Result<(), UnsupportedKey> {
+        Curve::of(method).map(|_| ())
+    }
+}
+
+/// Key curve supported by the `ecdsa-xi-2023` cryptosuite.
+///
+/// Curves are ordered by security level, so that a verifier can require a
+/// minimum curve (see
+/// [`VerificationParameters::require_min_curve`](crate::optical_barcode_credential::VerificationParameters::require_min_curve)).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Curve {
+    /// NIST P-256, signing with ES256.
+    P256,
+
+    /// NIST P-384, signing with ES384.
+    P384,
+}
+
+impl Curve {
+    /// Returns the curve of the given verification method key.
+    ///
+    /// The curve is identified from the multicodec prefix of the key, without
+    /// decoding it, so that keys on curves `ssi` is built without are still
+    /// reported as such.
+    pub fn of(method: &Multikey) -> Result<Self, UnsupportedKey> {
+        let (_, bytes) = method
+            .public_key
+            .encoded
+            .decode()
+            .map_err(|_| UnsupportedKey::Invalid)?;
+        Self::from_multicodec(&bytes)
+    }
+
+    /// Returns the curve of the given multicodec encoded public key.
+    pub fn from_multicodec(bytes: &[u8]) -> Result<Self, UnsupportedKey> {
+        // Unsigned varint multicodec prefixes.
+        match bytes {
+            [0x80, 0x24, ..] => Ok(Self::P256),
+            [0x81, 0x24, ..] => Ok(Self::P384),
+            [0xe7, 0x01, ..] => Err(UnsupportedKey::Curve("secp256k1")),
+            [0xed, 0x01, ..] => Err(UnsupportedKey::Curve("Ed25519")),
+            [0x82, 0x24, ..] => Err(UnsupportedKey::Curve("P-521")),
+            _ => Err(UnsupportedKey::Invalid),
+        }
+    }
+}
+
+impl Curve {
+    /// Length in bytes of the digests computed by the hash function of this
+    /// curve.
+    pub fn digest_len(&self) -> usize {
+        match self {
+            Self::P256 => 32,
+            Self::P384 => 48,
+        }
+    }
+}
+
+impl fmt::Display for Curve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::P256 => f.write_str("P-256"),
+            Self::P384 => f.write_str("P-384"),
+        }
+    }
+}
+
+/// Returns the `ecdsa-xi-2023` proof options for the given verification
+/// method, with the `assertionMethod` proof purpose.
+///
+/// The proof type and cryptosuite are set by [`EcdsaXi2023`] when the options
+/// are turned into a proof configuration, so they cannot be wrong.
+pub fn proof_options(vm: IriBuf) -> ProofOptions<Multikey, ()> {
+    ProofOptions::from_method(vm.into())
+}
+
+/// Returns the `ecdsa-xi-2023` proof options for the given verification
+/// method, with the given proof `created` and `expires` dates.
+///
+/// See [`proof_options`].
+pub fn proof_options_with(
+    vm: IriBuf,
+    created: Option<DateTime<Utc>>,
+    expires: Option<DateTime<Utc>>,
+) -> ProofOptions<Multikey, ()> {
+    let mut options = proof_options(vm);
+    options.created = created.map(Into::into);
+    options.expires = expires.map(Into::into);
+    options
+}
+
+/// Error returned when a key cannot be used with the `ecdsa-xi-2023`
+/// cryptosuite.
+#[derive(Debug, thiserror::Error)]
+pub enum UnsupportedKey {
+    /// The key cannot be decoded, or its multicodec is unknown.
+    #[error("invalid or unsupported multikey")]
+    Invalid,
+
+    /// The key is on the given curve, not supported by the cryptosuite.
+    #[error("unsupported key curve {0}: ecdsa-xi-2023 only supports P-256 and P-384")]
+    Curve(&'static str),
+}
+
+/// `ecdsa-xi-2023` extra information, signed along with the credential.
 #[derive(Debug, Clone)]
-pub struct ExtraInformation(pub Vec<u8>);
+pub enum ExtraInformation {
+    /// Optical data, hashed by the cryptosuite with the hash function of the
+    /// key curve.
+    OpticalData(Vec<u8>),
+
+    /// Digest of the optical data, already computed with the hash function
+    /// of the key curve: SHA-256 (32 bytes) for P-256 keys, SHA-384 (48
+    /// bytes) for P-384 keys.
+    ///
+    /// Hashing fails with [`HashingError::InvalidKey`] if the digest length
+    /// does not match the key curve.
+    OpticalDataHash(Vec<u8>),
+}
 
+/// `ecdsa-xi-2023` configuration algorithm.
+///
+/// The extra information is passed as signature and verification options,
+/// and handed over as is to the transformation algorithm.
 pub struct EcdsaXi2023ConfigurationAlgorithm;
 
 impl ConfigurationAlgorithm<EcdsaXi2023> for EcdsaXi2023ConfigurationAlgorithm {
@@ -87,11 +221,35 @@ impl ConfigurationAlgorithm<EcdsaXi2023> for EcdsaXi2023ConfigurationAlgorithm {
     }
 }
 
+/// Transformed data, along with the extra information to sign.
+///
+/// This is the output of [`EcdsaXi2023TransformationAlgorithm`] and the input
+/// of [`EcdsaXi2023HashingAlgorithm`]. Sibling cryptosuites binding other
+/// extra information can reuse it with their own hashing algorithm.
+#[derive(Debug, Clone)]
 pub struct WithExtraInformation<T> {
-    data: T,
-    extra_information: Vec<u8>,
+    /// Transformed data, i.e. the canonical claims and proof configuration.
+    pub data: T,
+
+    /// Extra information, hashed separately from the data.
+    pub extra_information: ExtraInformation,
+}
+
+impl<T> WithExtraInformation<T> {
+    pub fn new(data: T, extra_information: ExtraInformation) -> Self {
+        Self {
+            data,
+            extra_information,
+        }
+    }
 }
 
+/// `ecdsa-xi-2023` transformation algorithm.
+///
+/// Canonicalizes the credential and proof configuration with RDFC-1.0, as in
+/// `ecdsa-rdfc-2019`, and attaches the extra information to the result.
+///
+/// See: <https://w3c-ccg.github.io/vc-barcodes/#transformation-ecdsa-xi-2023>
 pub struct EcdsaXi2023TransformationAlgorithm;
 
 impl TransformationAlgorithm<EcdsaXi2023> for EcdsaXi2023TransformationAlgorithm {
@@ -128,11 +286,25 @@ where
                     .map_err(TransformationError::ProofConfigurationExpansion)?
                     .nquads_lines(),
             },
-            extra_information: transformation_options.0,
+            extra_information: transformation_options,
         })
     }
 }
 
+/// `ecdsa-xi-2023` hashing algorithm.
+///
+/// As in `ecdsa-rdfc-2019`, the hash function is chosen by the key curve:
+/// SHA-256 for P-256 and SHA-384 for P-384. It also applies to the extra
+/// information, so that for P-384 keys the optical data (always a SHA-256
+/// digest, see
+/// [`create_optical_data`](crate::optical_barcode_credential::OpticalBarcodeCredentialSubject::create_optical_data))
+/// is hashed a second time with SHA-384. This is what the specification
+/// prescribes, not a truncation.
+///
+/// The output is made of three fixed-length segments, without any separator:
+/// see [`EcdsaXi2023Hash`].
+///
+/// See: <https://w3c-ccg.github.io/vc-barcodes/#hashing-ecdsa-xi-2023>
 pub struct EcdsaXi2023HashingAlgorithm;
 
 impl HashingAlgorithm<EcdsaXi2023> for EcdsaXi2023HashingAlgorithm {
@@ -143,66 +315,99 @@ impl HashingAlgorithm<EcdsaXi2023> for EcdsaXi2023HashingAlgorithm {
         _proof_configuration: ProofConfigurationRef<EcdsaXi2023>,
         verification_method: &Multikey,
     ) -> Result<Self::Output, HashingError> {
-        match verification_method
-            .public_key
-            .decode()
-            .map_err(|_| HashingError::InvalidKey)?
-        {
-            multikey::DecodedMultikey::P256(_) => {
-                let proof_configuration_hash = input
-                    .data
-                    .configuration
-                    .iter()
-                    .fold(Sha256::new(), |h, line| h.chain_update(line.as_bytes()))
-                    .finalize();
-
-                let claims_hash = input
-                    .data
-                    .claims
-                    .iter()
-                    .fold(Sha256::new(), |h, line| h.chain_update(line.as_bytes()))
-                    .finalize();
-
-                let rdf_hash = ConcatOutputSize::concat(proof_configuration_hash, claims_hash);
-
-                let optical_data_hash: [u8; 32] = Sha256::digest(input.extra_information).into();
-
-                let mut hash = [0; 32 * 3];
-                hash[..64].copy_from_slice(&rdf_hash);
-                hash[64..].copy_from_slice(&optical_data_hash);
-
-                Ok(EcdsaXi2023Hash::Sha256(hash))
-            }
-            multikey::DecodedMultikey::P384(_) => {
-                let proof_configuration_hash = input
-                    .data
-                    .configuration
-                    .iter()
-                    .fold(Sha384::new(), |h, line| h.chain_update(line.as_bytes()))
-                    .finalize();
-
-                let claims_hash = input
-                    .data
-                    .claims
-                    .iter()
-                    .fold(Sha384::new(), |h, line| h.chain_update(line.as_bytes()))
-                    .finalize();
-
-                let rdf_hash = ConcatOutputSize::concat(proof_configuration_hash, claims_hash);
-
-                let optical_data_hash: [u8; 48] = Sha384::digest(input.extra_information).into();
-
-                let mut hash = [0; 48 * 3];
-                hash[..96].copy_from_slice(&rdf_hash);
-                hash[96..].copy_from_slice(&optical_data_hash);
-
-                Ok(EcdsaXi2023Hash::Sha384(hash))
-            }
-            _ => Err(HashingError::InvalidKey),
+        let curve = Curve::of(verification_method).map_err(|_| HashingError::InvalidKey)?;
+        hash_with_curve(&input, curve)
+    }
+}
+
+/// Hashes the transformed data with the hash function of the given curve.
+///
+/// The output is the concatenation of three segments, in this order:
+///   1. the digest of the canonical proof configuration,
+///   2. the digest of the canonical credential,
+///   3. the digest of the extra information.
+///
+/// This layout is fixed by the specification, so no domain separation tag can
+/// be added between the segments without breaking interoperability. None is
+/// needed: all three segments are outputs of the same hash function, hence
+/// have the same fixed length (32 bytes for P-256, 48 bytes for P-384), and
+/// the curve is given by the verification method, not by the signed data.
+/// Two distinct segment triples can never produce the same concatenation.
+fn hash_with_curve(
+    input: &WithExtraInformation<CanonicalClaimsAndConfiguration>,
+    curve: Curve,
+) -> Result<EcdsaXi2023Hash, HashingError> {
+    match curve {
+        Curve::P256 => {
+            let proof_configuration_hash = input
+                .data
+                .configuration
+                .iter()
+                .fold(Sha256::new(), |h, line| h.chain_update(line.as_bytes()))
+                .finalize();
+
+            let claims_hash = input
+                .data
+                .claims
+                .iter()
+                .fold(Sha256::new(), |h, line| h.chain_update(line.as_bytes()))
+                .finalize();
+
+            let rdf_hash = ConcatOutputSize::concat(proof_configuration_hash, claims_hash);
+
+            let optical_data_hash: [u8; 32] = match &input.extra_information {
+                ExtraInformation::OpticalData(data) => Sha256::digest(data).into(),
+                ExtraInformation::OpticalDataHash(hash) => hash
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| HashingError::InvalidKey)?,
+            };
+
+            let mut hash = [0; 32 * 3];
+            hash[..64].copy_from_slice(&rdf_hash);
+            hash[64..].copy_from_slice(&optical_data_hash);
+
+            Ok(EcdsaXi2023Hash::Sha256(hash))
+        }
+        Curve::P384 => {
+            let proof_configuration_hash = input
+                .data
+                .configuration
+                .iter()
+                .fold(Sha384::new(), |h, line| h.chain_update(line.as_bytes()))
+                .finalize();
+
+            let claims_hash = input
+                .data
+                .claims
+                .iter()
+                .fold(Sha384::new(), |h, line| h.chain_update(line.as_bytes()))
+                .finalize();
+
+            let rdf_hash = ConcatOutputSize::concat(proof_configuration_hash, claims_hash);
+
+            let optical_data_hash: [u8; 48] = match &input.extra_information {
+                ExtraInformation::OpticalData(data) => Sha384::digest(data).into(),
+                ExtraInformation::OpticalDataHash(hash) => hash
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| HashingError::InvalidKey)?,
+            };
+
+            let mut hash = [0; 48 * 3];
+            hash[..96].copy_from_slice(&rdf_hash);
+            hash[96..].copy_from_slice(&optical_data_hash);
+
+            Ok(EcdsaXi2023Hash::Sha384(hash))
         }
     }
 }
 
+/// `ecdsa-xi-2023` hash: the proof configuration, claims and extra
+/// information digests, concatenated.
+///
+/// The segments are not separated, as prescribed by the specification. This
+/// is unambiguous since each of them is a digest of the same fixed length.
 #[derive(Debug, Clone, Copy)]
 pub enum EcdsaXi2023Hash {
     Sha256([u8; 32 * 3]),
@@ -217,3 +422,64 @@ impl AsRef<[u8]> for EcdsaXi2023Hash {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use sha2::{Digest, Sha256, Sha384};
+    use ssi::claims::data_integrity::canonicalization::CanonicalClaimsAndConfiguration;
+
+    use super::{hash_with_curve, Curve, EcdsaXi2023Hash, ExtraInformation, WithExtraInformation};
+
+    fn input(
+        extra_information: ExtraInformation,
+    ) -> WithExtraInformation<CanonicalClaimsAndConfiguration> {
+        WithExtraInformation::new(
+            CanonicalClaimsAndConfiguration {
+                claims: vec!["<urn:a> <urn:b> <urn:c> .\n".to_owned()],
+                configuration: vec!["<urn:d> <urn:e> <urn:f> .\n".to_owned()],
+            },
+            extra_information,
+        )
+    }
+
+    #[test]
+    fn hash_layout() {
+        let optical_data = b"optical data".to_vec();
+
+        let EcdsaXi2023Hash::Sha256(hash) = hash_with_curve(
+            &input(ExtraInformation::OpticalData(optical_data.clone())),
+            Curve::P256,
+        )
+        .unwrap() else {
+            panic!("expected a SHA-256 hash")
+        };
+        assert_eq!(hash[..32], *Sha256::digest("<urn:d> <urn:e> <urn:f> .\n"));
+        assert_eq!(hash[32..64], *Sha256::digest("<urn:a> <urn:b> <urn:c> .\n"));
+        assert_eq!(hash[64..], *Sha256::digest(&optical_data));
+
+        let EcdsaXi2023Hash::Sha384(hash) = hash_with_curve(
+            &input(ExtraInformation::OpticalData(optical_data.clone())),
+            Curve::P384,
+        )
+        .unwrap() else {
+            panic!("expected a SHA-384 hash")
+        };
+        assert_eq!(hash[..48], *Sha384::digest("<urn:d> <urn:e> <urn:f> .\n"));
+        assert_eq!(hash[48..96], *Sha384::digest("<urn:a> <urn:b> <urn:c> .\n"));
+        assert_eq!(hash[96..], *Sha384::digest(&optical_data));
+
+        let from_digest = hash_with_curve(
+            &input(ExtraInformation::OpticalDataHash(
+                Sha256::digest(&optical_data).to_vec(),
+            )),
+            Curve::P256,
+        )
+        .unwrap();
+        let from_data = hash_with_curve(
+            &input(ExtraInformation::OpticalData(optical_data)),
+            Curve::P256,
+        )
+        .unwrap();
+        assert_eq!(from_digest.as_ref(), from_data.as_ref())
+    }
+}
@@ -5,17 +5,42 @@
 //!
 //! The `examples` folder contains a few examples showing how to create and
 //! verify VCBs.
+//!
+//! Disabling the default `std` feature restricts the library to the
+//! `no_std` + `alloc` compatible codecs: the [`mrz`] QR-code payload encoding,
+//! the [`aamva::dlid`] PDF417 file format and the [`source`] payload adapters.
+//!
+//! The `testing` feature provides deterministic signing helpers for
+//! reproducible tests.
+#![cfg_attr(not(feature = "std"), no_std)]
+extern crate alloc;
+
+#[cfg(feature = "std")]
 pub use ssi::claims::chrono::{DateTime, Utc};
 
 pub mod aamva;
+#[cfg(feature = "std")]
+pub mod did_key;
+#[cfg(feature = "std")]
 pub mod ecdsa_xi_2023;
+pub mod io;
 pub mod mrz;
+#[cfg(feature = "std")]
 pub mod optical_barcode_credential;
+pub mod source;
+#[cfg(feature = "std")]
 pub mod terse_bitstring_status_list_entry;
+#[cfg(feature = "testing")]
+pub mod testing;
 
+#[cfg(feature = "std")]
 pub use aamva::AamvaDriversLicenseScannableInformation;
+#[cfg(feature = "std")]
+pub use did_key::DidKeyResolver;
+#[cfg(feature = "std")]
 pub use ecdsa_xi_2023::EcdsaXi2023;
-pub use mrz::{MachineReadableZone, MRZ};
+pub use mrz::{MachineReadableZone, MrzBuilder, MrzLines, MRZ};
+#[cfg(feature = "std")]
 pub use optical_barcode_credential::{
     create, create_from_optical_data, verify, OpticalBarcodeCredential,
 };
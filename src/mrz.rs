@@ -1,36 +1,539 @@
-use std::collections::HashMap;
+use alloc::{format, string::String, vec::Vec};
+use core::fmt;
 
-use lazy_static::lazy_static;
+#[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
-use sha2::{Digest, Sha256};
+#[cfg(feature = "std")]
 use ssi::security::multibase;
 
+#[cfg(feature = "std")]
 use crate::optical_barcode_credential::OpticalBarcodeCredentialSubject;
 
 pub type MRZ = [[u8; 30]; 3];
 
-#[derive(Debug, thiserror::Error)]
-#[error("invalid QR code payload")]
+/// Validated Machine Readable Zone lines.
+///
+/// Each of the three lines is exactly 30 characters long and only contains
+/// characters from the MRZ character set (`A` to `Z`, `0` to `9` and `<`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MrzLines(MRZ);
+
+impl MrzLines {
+    /// Validates the given lines.
+    pub fn new(lines: [&[u8]; 3]) -> Result<Self, InvalidMrz> {
+        let mut result = [[0u8; 30]; 3];
+
+        for (i, (line, bytes)) in result.iter_mut().zip(lines).enumerate() {
+            if bytes.len() != 30 {
+                return Err(InvalidMrz::LineLength {
+                    line: i + 1,
+                    len: bytes.len(),
+                });
+            }
+
+            if let Some(j) = bytes.iter().position(|&b| !is_mrz_char(b)) {
+                return Err(InvalidMrz::InvalidCharacter {
+                    line: i + 1,
+                    column: j + 1,
+                    byte: bytes[j],
+                });
+            }
+
+            line.copy_from_slice(bytes);
+        }
+
+        Ok(Self(result))
+    }
+
+    pub fn as_mrz(&self) -> &MRZ {
+        &self.0
+    }
+
+    pub fn into_mrz(self) -> MRZ {
+        self.0
+    }
+}
+
+fn is_mrz_char(b: u8) -> bool {
+    b.is_ascii_uppercase() || b.is_ascii_digit() || b == b'<'
+}
+
+impl core::ops::Deref for MrzLines {
+    type Target = MRZ;
+
+    fn deref(&self) -> &MRZ {
+        &self.0
+    }
+}
+
+impl From<MrzLines> for MRZ {
+    fn from(value: MrzLines) -> Self {
+        value.0
+    }
+}
+
+/// Parses raw MRZ bytes, either as three lines separated by line breaks
+/// (`\n` or `\r\n`, with an optional final line break), or as 90 contiguous
+/// bytes.
+impl TryFrom<&[u8]> for MrzLines {
+    type Error = InvalidMrz;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        let value = value.strip_suffix(b"\n").unwrap_or(value);
+        let value = value.strip_suffix(b"\r").unwrap_or(value);
+
+        if value.len() == 90 && !value.contains(&b'\n') {
+            return Self::new([&value[..30], &value[30..60], &value[60..]]);
+        }
+
+        let mut lines = value
+            .split(|&b| b == b'\n')
+            .map(|line| line.strip_suffix(b"\r").unwrap_or(line));
+
+        match (lines.next(), lines.next(), lines.next(), lines.next()) {
+            (Some(a), Some(b), Some(c), None) => Self::new([a, b, c]),
+            _ => Err(InvalidMrz::LineCount(value.split(|&b| b == b'\n').count())),
+        }
+    }
+}
+
+impl TryFrom<[&str; 3]> for MrzLines {
+    type Error = InvalidMrz;
+
+    fn try_from(value: [&str; 3]) -> Result<Self, Self::Error> {
+        Self::new(value.map(str::as_bytes))
+    }
+}
+
+/// Invalid Machine Readable Zone.
+///
+/// Line and column numbers start at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidMrz {
+    /// The zone does not have exactly three lines.
+    LineCount(usize),
+
+    /// A line is not exactly 30 characters long.
+    LineLength { line: usize, len: usize },
+
+    /// A line contains a character outside of the MRZ character set.
+    InvalidCharacter {
+        line: usize,
+        column: usize,
+        byte: u8,
+    },
+}
+
+impl fmt::Display for InvalidMrz {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LineCount(n) => write!(f, "expected 3 MRZ lines, found {n}"),
+            Self::LineLength { line, len } => {
+                write!(f, "MRZ line {line} has {len} characters, expected 30")
+            }
+            Self::InvalidCharacter { line, column, byte } => write!(
+                f,
+                "invalid MRZ character {:?} at line {line}, column {column}",
+                char::from(*byte)
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidMrz {}
+
+/// Computes the ICAO 9303 check digit of the given MRZ characters.
+///
+/// Returns the check digit as an ASCII digit.
+pub fn check_digit(data: &[u8]) -> u8 {
+    const WEIGHTS: [u32; 3] = [7, 3, 1];
+
+    let sum: u32 = data
+        .iter()
+        .zip(WEIGHTS.iter().cycle())
+        .map(|(&b, w)| {
+            let value = match b {
+                b'0'..=b'9' => b - b'0',
+                b'A'..=b'Z' => b - b'A' + 10,
+                _ => 0,
+            };
+
+            value as u32 * w
+        })
+        .sum();
+
+    b'0' + (sum % 10) as u8
+}
+
+/// ICAO 9303 TD1 Machine Readable Zone builder.
+///
+/// Lays out the three 30-character lines, padding fields with the `<`
+/// filler and computing the check digits. Fields must only contain MRZ
+/// characters (`A` to `Z`, `0` to `9` and `<`), except for the name
+/// components where spaces are replaced by fillers. Dates are formatted as
+/// `YYMMDD`.
+#[derive(Debug, Default, Clone)]
+pub struct MrzBuilder {
+    document_code: String,
+    issuing_state: String,
+    document_number: String,
+    optional_data_1: String,
+    birth_date: String,
+    sex: Option<u8>,
+    expiry_date: String,
+    nationality: String,
+    optional_data_2: String,
+    primary_identifier: String,
+    secondary_identifier: String,
+}
+
+impl MrzBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the document code (up to 2 characters, e.g. `"I"` or `"ID"`).
+    pub fn document_code(mut self, value: impl Into<String>) -> Self {
+        self.document_code = value.into();
+        self
+    }
+
+    /// Sets the issuing state or organization (up to 3 characters).
+    pub fn issuing_state(mut self, value: impl Into<String>) -> Self {
+        self.issuing_state = value.into();
+        self
+    }
+
+    /// Sets the document number (up to 9 characters).
+    pub fn document_number(mut self, value: impl Into<String>) -> Self {
+        self.document_number = value.into();
+        self
+    }
+
+    /// Sets the optional data of the first line (up to 15 characters).
+    pub fn optional_data_1(mut self, value: impl Into<String>) -> Self {
+        self.optional_data_1 = value.into();
+        self
+    }
+
+    /// Sets the date of birth (`YYMMDD`).
+    pub fn birth_date(mut self, value: impl Into<String>) -> Self {
+        self.birth_date = value.into();
+        self
+    }
+
+    /// Sets the sex (`M`, `F` or `<` if unspecified, the default).
+    pub fn sex(mut self, value: u8) -> Self {
+        self.sex = Some(value);
+        self
+    }
+
+    /// Sets the date of expiry (`YYMMDD`).
+    pub fn expiry_date(mut self, value: impl Into<String>) -> Self {
+        self.expiry_date = value.into();
+        self
+    }
+
+    /// Sets the nationality (up to 3 characters).
+    pub fn nationality(mut self, value: impl Into<String>) -> Self {
+        self.nationality = value.into();
+        self
+    }
+
+    /// Sets the optional data of the second line (up to 11 characters).
+    pub fn optional_data_2(mut self, value: impl Into<String>) -> Self {
+        self.optional_data_2 = value.into();
+        self
+    }
+
+    /// Sets the name of the holder.
+    ///
+    /// Together, the primary and secondary identifiers must fit the 30
+    /// characters of the third line, separated by `<<`.
+    pub fn name(mut self, primary: impl Into<String>, secondary: impl Into<String>) -> Self {
+        self.primary_identifier = primary.into();
+        self.secondary_identifier = secondary.into();
+        self
+    }
+
+    /// Assembles the Machine Readable Zone.
+    pub fn build(&self) -> Result<MRZ, InvalidMrzField> {
+        let mut mrz = [[b'<'; 30]; 3];
+
+        let [line1, line2, line3] = &mut mrz;
+
+        put(line1, 0, 2, "document code", &self.document_code, true)?;
+        put(line1, 2, 3, "issuing state", &self.issuing_state, true)?;
+        put(line1, 5, 9, "document number", &self.document_number, true)?;
+        line1[14] = check_digit(&line1[5..14]);
+        put(line1, 15, 15, "optional data", &self.optional_data_1, false)?;
+
+        put_date(line2, 0, "date of birth", &self.birth_date)?;
+        line2[6] = check_digit(&line2[0..6]);
+        match self.sex {
+            Some(b @ (b'M' | b'F' | b'<')) => line2[7] = b,
+            Some(b) => {
+                return Err(InvalidMrzField::InvalidCharacter {
+                    field: "sex",
+                    byte: b,
+                })
+            }
+            None => (),
+        }
+        put_date(line2, 8, "date of expiry", &self.expiry_date)?;
+        line2[14] = check_digit(&line2[8..14]);
+        put(line2, 15, 3, "nationality", &self.nationality, true)?;
+        put(line2, 18, 11, "optional data", &self.optional_data_2, false)?;
+
+        let mut composite = Vec::with_capacity(25 + 7 + 7 + 11);
+        composite.extend_from_slice(&line1[5..30]);
+        composite.extend_from_slice(&line2[0..7]);
+        composite.extend_from_slice(&line2[8..15]);
+        composite.extend_from_slice(&line2[18..29]);
+        line2[29] = check_digit(&composite);
+
+        if self.primary_identifier.is_empty() {
+            return Err(InvalidMrzField::Missing("name"));
+        }
+
+        let name = if self.secondary_identifier.is_empty() {
+            self.primary_identifier.replace(' ', "<")
+        } else {
+            format!(
+                "{}<<{}",
+                self.primary_identifier.replace(' ', "<"),
+                self.secondary_identifier.replace(' ', "<")
+            )
+        };
+        put(line3, 0, 30, "name", &name, true)?;
+
+        Ok(mrz)
+    }
+}
+
+/// Writes `value` into `line[offset..offset + width]`, leaving the remaining
+/// characters as fillers.
+fn put(
+    line: &mut [u8; 30],
+    offset: usize,
+    width: usize,
+    field: &'static str,
+    value: &str,
+    required: bool,
+) -> Result<(), InvalidMrzField> {
+    if required && value.is_empty() {
+        return Err(InvalidMrzField::Missing(field));
+    }
+
+    if value.len() > width {
+        return Err(InvalidMrzField::Overflow { field, max: width });
+    }
+
+    if let Some(&byte) = value.as_bytes().iter().find(|&&b| !is_mrz_char(b)) {
+        return Err(InvalidMrzField::InvalidCharacter { field, byte });
+    }
+
+    line[offset..offset + value.len()].copy_from_slice(value.as_bytes());
+    Ok(())
+}
+
+fn put_date(
+    line: &mut [u8; 30],
+    offset: usize,
+    field: &'static str,
+    value: &str,
+) -> Result<(), InvalidMrzField> {
+    if let Some(&byte) = value.as_bytes().iter().find(|b| !b.is_ascii_digit()) {
+        return Err(InvalidMrzField::InvalidCharacter { field, byte });
+    }
+
+    if !value.is_empty() && value.len() != 6 {
+        return Err(InvalidMrzField::InvalidDate(field));
+    }
+
+    put(line, offset, 6, field, value, true)
+}
+
+/// Invalid [`MrzBuilder`] field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidMrzField {
+    /// A required field is missing.
+    Missing(&'static str),
+
+    /// A field is longer than its fixed width.
+    Overflow { field: &'static str, max: usize },
+
+    /// A field contains a character outside of the MRZ character set.
+    InvalidCharacter { field: &'static str, byte: u8 },
+
+    /// A date is not formatted as `YYMMDD`.
+    InvalidDate(&'static str),
+}
+
+impl fmt::Display for InvalidMrzField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing(field) => write!(f, "missing MRZ {field}"),
+            Self::Overflow { field, max } => {
+                write!(f, "MRZ {field} is longer than {max} characters")
+            }
+            Self::InvalidCharacter { field, byte } => write!(
+                f,
+                "invalid character {:?} in MRZ {field}",
+                char::from(*byte)
+            ),
+            Self::InvalidDate(field) => write!(f, "MRZ {field} is not formatted as YYMMDD"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidMrzField {}
+
+#[derive(Debug)]
 pub struct InvalidQrCodePayload;
 
+impl fmt::Display for InvalidQrCodePayload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid QR code payload")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidQrCodePayload {}
+
+#[cfg(feature = "std")]
 impl From<multibase::Error> for InvalidQrCodePayload {
     fn from(_value: multibase::Error) -> Self {
         Self
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-#[serde(tag = "type", rename_all = "camelCase")]
+/// Machine Readable Zone credential subject.
+///
+/// The subject carries no claim: the credential only protects the MRZ lines,
+/// through the optical data. In particular, the `https://w3id.org/vdl/v2`
+/// driver's license claims cannot travel in the credential, since that
+/// context cannot be used alongside the VC Barcodes context: both protect
+/// different definitions of the `OpticalBarcodeCredential` and
+/// `TerseBitstringStatusListEntry` terms.
+#[derive(Debug)]
+#[cfg_attr(feature = "std", derive(Serialize))]
+#[cfg_attr(feature = "std", serde(tag = "type", rename_all = "camelCase"))]
 pub struct MachineReadableZone {}
 
+/// Checks the subject `type`, which the derived implementation would ignore
+/// for an internally tagged struct.
+#[cfg(feature = "std")]
+impl<'de> Deserialize<'de> for MachineReadableZone {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct Tagged {
+            #[serde(rename = "type")]
+            type_: String,
+        }
+
+        let tagged = Tagged::deserialize(deserializer)?;
+        if tagged.type_ == <Self as OpticalBarcodeCredentialSubject>::TYPE {
+            Ok(Self {})
+        } else {
+            Err(serde::de::Error::invalid_value(
+                serde::de::Unexpected::Str(&tagged.type_),
+                &"`MachineReadableZone`",
+            ))
+        }
+    }
+}
+
 impl MachineReadableZone {
+    /// Encodes the given CBOR-LD bytes into a `VC1-` prefixed base45 string,
+    /// to be rendered as a QR code in alphanumeric mode.
     pub fn encode_qr_code_payload(bytes: &[u8]) -> String {
         format!("VC1-{}", multibase45_encode(bytes))
     }
 
+    /// Returns the length of the payload produced by
+    /// [`Self::encode_qr_code_payload`] for the given CBOR-LD bytes, without
+    /// encoding them.
+    ///
+    /// Every pair of bytes is encoded as 3 base45 characters, and a trailing
+    /// byte as 2, after the `VC1-` prefix and multibase `R` header. Since the
+    /// payload only uses QR code alphanumeric characters, this can be used to
+    /// pick a QR code version (version 40 holds at most 4296 alphanumeric
+    /// characters at the lowest error correction level).
+    pub fn estimate_qr_payload_len(cbor_bytes: &[u8]) -> usize {
+        let n = cbor_bytes.len();
+        "VC1-R".len() + n / 2 * 3 + n % 2 * 2
+    }
+
+    /// Decodes a `VC1-` prefixed base45 string into CBOR-LD bytes.
+    ///
+    /// Accepts payloads produced by either [`Self::encode_qr_code_payload`]
+    /// or [`Self::encode_aztec_payload`].
     pub fn decode_qr_code_payload(value: &str) -> Result<Vec<u8>, InvalidQrCodePayload> {
         let base45 = value.strip_prefix("VC1-").ok_or(InvalidQrCodePayload)?;
-        multibase45_decode(base45).map_err(Into::into)
+        multibase45_decode(base45)
+    }
+
+    /// Decodes a `VC1-` prefixed base45 string into CBOR-LD bytes, also
+    /// accepting legacy payloads missing the multibase `R` prefix.
+    ///
+    /// Some early encoders emitted raw base45 after `VC1-`. The multibase form
+    /// is tried first, then the whole payload is decoded as raw base45. Since
+    /// `R` is also a base45 digit, a raw payload starting with `R` whose
+    /// remainder happens to be valid base45 is decoded as multibase (and
+    /// yields the wrong bytes): prefer [`Self::decode_qr_code_payload`]
+    /// unless such encoders must be supported.
+    pub fn decode_qr_code_payload_compat(value: &str) -> Result<Vec<u8>, InvalidQrCodePayload> {
+        let base45 = value.strip_prefix("VC1-").ok_or(InvalidQrCodePayload)?;
+        multibase45_decode(base45).or_else(|_| {
+            let mut buffer = Vec::new();
+            base45_decode_to(&mut buffer, base45)?;
+            Ok(buffer)
+        })
+    }
+
+    /// Decodes a `VC1-` prefixed base45 string into CBOR-LD bytes, tolerating
+    /// scanner or OCR artifacts.
+    ///
+    /// Leading and trailing ASCII whitespace is trimmed, and line breaks
+    /// (`\r`, `\n`) are removed anywhere in the payload. Since the space
+    /// character is itself a base45 digit, spaces inside the payload are
+    /// kept, and a payload legitimately ending with a space digit cannot be
+    /// decoded by this function: use [`Self::decode_qr_code_payload`] when the
+    /// scanner output is known to be exact.
+    pub fn decode_qr_code_payload_lenient(value: &str) -> Result<Vec<u8>, InvalidQrCodePayload> {
+        let value: String = value
+            .trim_matches(|c: char| c.is_ascii_whitespace())
+            .chars()
+            .filter(|c| !matches!(c, '\r' | '\n'))
+            .collect();
+
+        Self::decode_qr_code_payload(&value)
+    }
+
+    /// Encodes the given CBOR-LD bytes into a string to be rendered as an
+    /// Aztec code.
+    ///
+    /// The payload uses the exact same `VC1-` framing and base45 alphabet as
+    /// [`Self::encode_qr_code_payload`], which Aztec encoders pack in their
+    /// upper/digit/punctuation modes. An error correction level of at least
+    /// 23% (the common default) is recommended for travel documents.
+    pub fn encode_aztec_payload(bytes: &[u8]) -> String {
+        Self::encode_qr_code_payload(bytes)
+    }
+
+    /// Decodes the payload of an Aztec code into CBOR-LD bytes.
+    ///
+    /// Since the framing is identical, this accepts payloads produced by
+    /// either [`Self::encode_aztec_payload`] or
+    /// [`Self::encode_qr_code_payload`].
+    pub fn decode_aztec_payload(value: &str) -> Result<Vec<u8>, InvalidQrCodePayload> {
+        Self::decode_qr_code_payload(value)
     }
 }
 
@@ -40,19 +543,27 @@ const BASE_45_TABLE: [char; 45] = [
     '%', '*', '+', '-', '.', '/', ':',
 ];
 
-lazy_static! {
-    static ref BASE_45_REVERSE_TABLE: HashMap<char, u16> = {
-        let mut table = HashMap::new();
+const BASE_45_REVERSE_TABLE: [u8; 128] = {
+    let mut table = [u8::MAX; 128];
 
-        for (i, c) in BASE_45_TABLE.iter().enumerate() {
-            table.insert(*c, i as u16);
-        }
+    let mut i = 0;
+    while i < BASE_45_TABLE.len() {
+        table[BASE_45_TABLE[i] as usize] = i as u8;
+        i += 1;
+    }
 
-        table
-    };
+    table
+};
+
+fn base45_digit(c: char) -> Result<u16, InvalidQrCodePayload> {
+    match BASE_45_REVERSE_TABLE.get(c as usize) {
+        Some(&d) if d != u8::MAX => Ok(d as u16),
+        _ => Err(InvalidQrCodePayload),
+    }
 }
 
-fn multibase45_encode(bytes: &[u8]) -> String {
+/// Encodes the given bytes using multibase base45 (`R` prefix).
+pub fn multibase45_encode(bytes: &[u8]) -> String {
     let mut result = String::new();
     result.push('R');
     base45_encode_to(&mut result, bytes);
@@ -88,68 +599,57 @@ fn base45_encode_to(buffer: &mut String, bytes: &[u8]) {
     }
 }
 
-fn multibase45_decode(value: &str) -> Result<Vec<u8>, multibase::Error> {
-    if value.is_empty() {
-        Err(multibase::Error::InvalidBaseString)
-    } else {
-        match value.as_bytes()[0] {
-            b'R' => {
-                let mut buffer = Vec::new();
-                base45_decode_to(&mut buffer, &value[1..])?;
-                Ok(buffer)
-            }
-            base => Err(multibase::Error::UnknownBase(base as char)),
+/// Decodes a multibase base45 (`R` prefix) string.
+pub fn multibase45_decode(value: &str) -> Result<Vec<u8>, InvalidQrCodePayload> {
+    match value.strip_prefix('R') {
+        Some(base45) => {
+            let mut buffer = Vec::new();
+            base45_decode_to(&mut buffer, base45)?;
+            Ok(buffer)
         }
+        None => Err(InvalidQrCodePayload),
     }
 }
 
-fn base45_decode_to(bytes: &mut Vec<u8>, value: &str) -> Result<(), multibase::Error> {
+fn base45_decode_to(bytes: &mut Vec<u8>, value: &str) -> Result<(), InvalidQrCodePayload> {
     let mut chars = value.chars();
 
     while let Some(c) = chars.next() {
-        let c = BASE_45_REVERSE_TABLE
-            .get(&c)
-            .ok_or(multibase::Error::InvalidBaseString)?;
+        let c = base45_digit(c)?;
         match chars.next() {
             Some(d) => {
-                let d = BASE_45_REVERSE_TABLE
-                    .get(&d)
-                    .ok_or(multibase::Error::InvalidBaseString)?;
+                let d = base45_digit(d)?;
                 match chars.next() {
                     Some(e) => {
-                        let e = BASE_45_REVERSE_TABLE
-                            .get(&e)
-                            .ok_or(multibase::Error::InvalidBaseString)?;
+                        let e = base45_digit(e)?;
                         let value = (c + d * 45)
-                            .checked_add(
-                                45u16
-                                    .checked_mul(e * 45)
-                                    .ok_or(multibase::Error::InvalidBaseString)?,
-                            )
-                            .ok_or(multibase::Error::InvalidBaseString)?;
+                            .checked_add(45u16.checked_mul(e * 45).ok_or(InvalidQrCodePayload)?)
+                            .ok_or(InvalidQrCodePayload)?;
                         let a = ((value & 0xff00) >> 8) as u8;
                         let b = (value & 0x00ff) as u8;
                         bytes.push(a);
                         bytes.push(b);
                     }
                     None => {
-                        let a = (c + d * 45) as u8;
+                        let a = u8::try_from(c + d * 45).map_err(|_| InvalidQrCodePayload)?;
                         bytes.push(a);
                     }
                 }
             }
-            None => return Err(multibase::Error::InvalidBaseString),
+            None => return Err(InvalidQrCodePayload),
         }
     }
 
     Ok(())
 }
 
+#[cfg(feature = "std")]
 unsafe impl OpticalBarcodeCredentialSubject for MachineReadableZone {
-    // type Context = VdlV2;
     type ExtraInformation = MRZ;
 
-    fn create_optical_data(&self, xi: &Self::ExtraInformation) -> [u8; 32] {
+    const TYPE: &'static str = "MachineReadableZone";
+
+    fn create_canonical_optical_data(&self, xi: &Self::ExtraInformation) -> Vec<u8> {
         let mut canonical_data = Vec::with_capacity(28 * 3);
 
         canonical_data.extend(&xi[0]);
@@ -159,6 +659,6 @@ unsafe impl OpticalBarcodeCredentialSubject for MachineReadableZone {
         canonical_data.extend(&xi[2]);
         canonical_data.push(b'\n');
 
-        Sha256::digest(canonical_data).into()
+        canonical_data
     }
 }
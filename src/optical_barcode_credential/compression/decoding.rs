@@ -1,36 +1,293 @@
+use std::fmt::{self, Write};
+
+use cbor_ld::CborValue;
+use json_syntax::Print;
 use ssi::claims::data_integrity::DataIntegrity;
 
 use crate::{
     ecdsa_xi_2023::EcdsaXi2023,
-    optical_barcode_credential::{OpticalBarcodeCredentialSubject, CONTEXT_LOADER},
-    OpticalBarcodeCredential,
+    optical_barcode_credential::{
+        OpticalBarcodeCredentialSubject, VerifiableOpticalBarcodeCredential, CONTEXT_LOADER,
+    },
+    AamvaDriversLicenseScannableInformation, MachineReadableZone, OpticalBarcodeCredential,
 };
 
+/// Decodes a CBOR-LD optical barcode credential.
+///
+/// The compression mode is read from the CBOR-LD tag, so documents encoded
+/// with either the VC Barcodes registry entry or no compression at all (see
+/// [`EncodeConfig`](super::EncodeConfig)) are accepted.
+///
+/// This is the counterpart of [`encode`](super::encode), for credentials
+/// embedded in a larger CBOR structure:
+///
+/// ```
+/// # use w3c_vc_barcodes::{optical_barcode_credential::{self, CborValue}, MachineReadableZone};
+/// # async fn example(envelope: CborValue) {
+/// if let CborValue::Array(items) = envelope {
+///     let vc = optical_barcode_credential::decode::<MachineReadableZone>(&items[1]).await;
+/// }
+/// # }
+/// ```
 pub async fn decode<T>(
-    cbor: &cbor_ld::CborValue,
+    cbor: &CborValue,
 ) -> Result<DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>, DecodeError>
 where
     T: OpticalBarcodeCredentialSubject,
 {
     let json = cbor_ld::decode(cbor, &*CONTEXT_LOADER).await?;
+    check_subject_type::<T>(&json)?;
     json_syntax::from_value(json).map_err(Into::into)
 }
 
+/// Decodes CBOR-LD optical barcode credential bytes.
+///
+/// See [`decode`] for the accepted compression modes. The default
+/// [`DecodeLimits`] apply.
 pub async fn decode_from_bytes<T>(
     bytes: &[u8],
 ) -> Result<DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>, DecodeError>
 where
     T: OpticalBarcodeCredentialSubject,
 {
-    let json = cbor_ld::decode_from_bytes(bytes, &*CONTEXT_LOADER).await?;
+    decode_from_bytes_with_limits(bytes, DecodeLimits::default()).await
+}
+
+/// Decodes CBOR-LD optical barcode credential bytes, with the given limits.
+///
+/// Barcodes are untrusted input: this bounds the nesting depth of the CBOR
+/// input and the size of the decompressed document, returning
+/// [`DecodeError::TooLarge`] if either is exceeded. The document size is
+/// checked after decompression, see [`DecodeLimits::max_json_bytes`].
+pub async fn decode_from_bytes_with_limits<T>(
+    bytes: &[u8],
+    limits: DecodeLimits,
+) -> Result<DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>, DecodeError>
+where
+    T: OpticalBarcodeCredentialSubject,
+{
+    let json = decode_json_from_bytes(bytes, limits).await?;
+    check_subject_type::<T>(&json)?;
     json_syntax::from_value(json).map_err(Into::into)
 }
 
+/// Decodes CBOR-LD optical barcode credential bytes into JSON-LD, without
+/// deserializing the credential.
+///
+/// Useful to inspect documents that decompress fine but fail the typed
+/// deserialization of [`decode_from_bytes`]. The default [`DecodeLimits`]
+/// apply.
+pub async fn decode_to_json(bytes: &[u8]) -> Result<json_syntax::Value, DecodeError> {
+    decode_json_from_bytes(bytes, DecodeLimits::default()).await
+}
+
+/// Limits applied when decoding CBOR-LD bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DecodeLimits {
+    /// Maximum size of the decoded JSON document, in bytes, once printed in
+    /// compact form.
+    ///
+    /// This is checked once the document is fully decompressed, before it is
+    /// deserialized. It bounds what is handed to the rest of the pipeline,
+    /// not the decompression itself, whose output grows linearly with the
+    /// input size.
+    pub max_json_bytes: usize,
+
+    /// Maximum nesting depth of the CBOR input.
+    pub max_depth: usize,
+}
+
+impl DecodeLimits {
+    pub const DEFAULT: Self = Self {
+        max_json_bytes: 256 * 1024,
+        max_depth: 32,
+    };
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+async fn decode_json_from_bytes(
+    bytes: &[u8],
+    limits: DecodeLimits,
+) -> Result<json_syntax::Value, DecodeError> {
+    let cbor: CborValue = ciborium::de::from_reader_with_recursion_limit(bytes, limits.max_depth)
+        .map_err(|e| match e {
+        ciborium::de::Error::RecursionLimitExceeded => DecodeError::TooLarge,
+        e => DecodeError::Cbor(e),
+    })?;
+
+    let json = cbor_ld::decode(&cbor, &*CONTEXT_LOADER).await?;
+
+    let mut counter = ByteCounter {
+        len: 0,
+        max: limits.max_json_bytes,
+    };
+    if write!(counter, "{}", json.compact_print()).is_err() {
+        return Err(DecodeError::TooLarge);
+    }
+
+    Ok(json)
+}
+
+/// Counts written bytes, failing once the maximum is exceeded.
+struct ByteCounter {
+    len: usize,
+    max: usize,
+}
+
+impl Write for ByteCounter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.len += s.len();
+        if self.len > self.max {
+            Err(fmt::Error)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Optical barcode credential of any supported credential subject type.
+#[derive(Debug)]
+pub enum DecodedVcb {
+    MachineReadableZone(VerifiableOpticalBarcodeCredential<MachineReadableZone>),
+    Aamva(VerifiableOpticalBarcodeCredential<AamvaDriversLicenseScannableInformation>),
+}
+
+/// Decodes a CBOR-LD optical barcode credential, detecting the credential
+/// subject type from its `type` property.
+pub async fn decode_any(cbor: &CborValue) -> Result<DecodedVcb, DecodeError> {
+    let json = cbor_ld::decode(cbor, &*CONTEXT_LOADER).await?;
+    decode_any_json(json)
+}
+
+/// Decodes CBOR-LD optical barcode credential bytes, detecting the
+/// credential subject type from its `type` property.
+///
+/// The default [`DecodeLimits`] apply.
+pub async fn decode_any_from_bytes(bytes: &[u8]) -> Result<DecodedVcb, DecodeError> {
+    let json = decode_json_from_bytes(bytes, DecodeLimits::default()).await?;
+    decode_any_json(json)
+}
+
+fn decode_any_json(json: json_syntax::Value) -> Result<DecodedVcb, DecodeError> {
+    let types = subject_types(&json);
+
+    if types.contains(&MachineReadableZone::TYPE) {
+        json_syntax::from_value(json)
+            .map(DecodedVcb::MachineReadableZone)
+            .map_err(Into::into)
+    } else if types.contains(&AamvaDriversLicenseScannableInformation::TYPE) {
+        json_syntax::from_value(json)
+            .map(DecodedVcb::Aamva)
+            .map_err(Into::into)
+    } else {
+        Err(DecodeError::UnknownSubjectType)
+    }
+}
+
+/// Returns the credential subject types, whether `type` is a single string
+/// or an array.
+fn subject_types(json: &json_syntax::Value) -> Vec<&str> {
+    match subject_type_value(json) {
+        Some(json_syntax::Value::Array(types)) => types
+            .iter()
+            .filter_map(json_syntax::Value::as_str)
+            .collect(),
+        Some(ty) => ty.as_str().into_iter().collect(),
+        None => Vec::new(),
+    }
+}
+
+fn subject_type_value(json: &json_syntax::Value) -> Option<&json_syntax::Value> {
+    let subject = json.as_object()?.get_unique("credentialSubject").ok()??;
+
+    let subject = match subject.as_array() {
+        Some(subjects) => subjects.first()?,
+        None => subject,
+    };
+
+    subject.as_object()?.get_unique("type").ok()?
+}
+
+/// Checks that the credential subject `type` includes `T::TYPE`, before
+/// deserializing the credential.
+///
+/// A missing or malformed `type` is left for the deserialization to report.
+fn check_subject_type<T>(json: &json_syntax::Value) -> Result<(), DecodeError>
+where
+    T: OpticalBarcodeCredentialSubject,
+{
+    let types = subject_types(json);
+
+    if types.is_empty() || types.contains(&T::TYPE) {
+        Ok(())
+    } else {
+        Err(DecodeError::SubjectTypeMismatch {
+            expected: T::TYPE,
+            found: types.join(", "),
+        })
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum DecodeError {
     #[error(transparent)]
     CborLd(#[from] cbor_ld::DecodeError),
 
     #[error(transparent)]
     Json(#[from] json_syntax::DeserializeError),
+
+    #[error("invalid CBOR: {0}")]
+    Cbor(#[source] ciborium::de::Error<std::io::Error>),
+
+    #[error("decoded document exceeds the decoding limits")]
+    TooLarge,
+
+    #[error("unknown credential subject type")]
+    UnknownSubjectType,
+
+    /// The credential subject is not of the requested type. Decoding may be
+    /// retried with the right subject type, or with [`decode_any`].
+    #[error("credential subject type mismatch: expected `{expected}`, found `{found}`")]
+    SubjectTypeMismatch {
+        expected: &'static str,
+        found: String,
+    },
+}
+
+impl DecodeError {
+    /// Checks if this is a [`Self::CborLd`] error.
+    pub fn is_cbor_ld(&self) -> bool {
+        matches!(self, Self::CborLd(_))
+    }
+
+    /// Checks if this is a [`Self::Json`] error.
+    pub fn is_json(&self) -> bool {
+        matches!(self, Self::Json(_))
+    }
+
+    /// Checks if this is a [`Self::Cbor`] error.
+    pub fn is_cbor(&self) -> bool {
+        matches!(self, Self::Cbor(_))
+    }
+
+    /// Checks if this is a [`Self::TooLarge`] error.
+    pub fn is_too_large(&self) -> bool {
+        matches!(self, Self::TooLarge)
+    }
+
+    /// Checks if this is a [`Self::UnknownSubjectType`] error.
+    pub fn is_unknown_subject_type(&self) -> bool {
+        matches!(self, Self::UnknownSubjectType)
+    }
+
+    /// Checks if this is a [`Self::SubjectTypeMismatch`] error.
+    pub fn is_subject_type_mismatch(&self) -> bool {
+        matches!(self, Self::SubjectTypeMismatch { .. })
+    }
 }
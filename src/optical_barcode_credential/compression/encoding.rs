@@ -1,4 +1,7 @@
+use std::io;
+
 use cbor_ld::{tables::RegistryEntry, CompressionMode, EncodeOptions};
+use json_syntax::Print;
 use ssi::claims::data_integrity::DataIntegrity;
 
 use crate::{
@@ -7,33 +10,231 @@ use crate::{
     OpticalBarcodeCredential,
 };
 
-fn encode_options() -> EncodeOptions {
-    EncodeOptions {
-        compression_mode: CompressionMode::Compressed(RegistryEntry::VcBarcodes),
-        ..Default::default()
+pub use cbor_ld::{CborValue, CompressionMode};
+
+/// CBOR-LD encoding configuration.
+#[derive(Debug, Clone)]
+pub struct EncodeConfig {
+    /// Compression mode.
+    ///
+    /// Defaults to the VC Barcodes registry entry. Use
+    /// [`CompressionMode::Uncompressed`] for debugging.
+    pub compression_mode: CompressionMode,
+}
+
+impl EncodeConfig {
+    pub fn new(compression_mode: CompressionMode) -> Self {
+        Self { compression_mode }
+    }
+
+    fn into_encode_options(self) -> EncodeOptions {
+        EncodeOptions {
+            compression_mode: self.compression_mode,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for EncodeConfig {
+    fn default() -> Self {
+        Self::new(CompressionMode::Compressed(RegistryEntry::VcBarcodes))
     }
 }
 
-pub async fn encode<T>(
+/// Encodes the given credential into a CBOR-LD value.
+///
+/// The value can be embedded into a larger CBOR structure before
+/// serialization:
+///
+/// ```
+/// # use w3c_vc_barcodes::{optical_barcode_credential::{self, CborValue, VerifiableOpticalBarcodeCredential}, MachineReadableZone};
+/// # async fn example(vc: &VerifiableOpticalBarcodeCredential<MachineReadableZone>) {
+/// let value = optical_barcode_credential::encode(vc).await;
+/// let envelope = CborValue::Array(vec![CborValue::Text("vcb".to_owned()), value]);
+///
+/// let mut bytes = Vec::new();
+/// ciborium::into_writer(&envelope, &mut bytes).unwrap();
+/// # }
+/// ```
+pub async fn encode<T>(vc: &DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>) -> CborValue
+where
+    T: OpticalBarcodeCredentialSubject,
+{
+    encode_with(vc, EncodeConfig::default()).await
+}
+
+/// Encodes the given credential into a CBOR-LD value, using the given
+/// configuration.
+pub async fn encode_with<T>(
     vc: &DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>,
-) -> cbor_ld::CborValue
+    config: EncodeConfig,
+) -> CborValue
 where
     T: OpticalBarcodeCredentialSubject,
 {
     let json = json_syntax::to_value(vc).unwrap();
-    cbor_ld::encode_with(&json, &*CONTEXT_LOADER, encode_options())
+    cbor_ld::encode_with(&json, &*CONTEXT_LOADER, config.into_encode_options())
         .await
         .unwrap()
 }
 
+/// Encodes the given credential into CBOR-LD bytes.
+///
+/// Encoding is deterministic: the same credential always produces the same
+/// bytes. See [`encode_to_bytes_checked`] to also verify that the output
+/// follows the CBOR deterministic encoding rules.
 pub async fn encode_to_bytes<T>(
     vc: &DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>,
 ) -> Vec<u8>
+where
+    T: OpticalBarcodeCredentialSubject,
+{
+    encode_to_bytes_with(vc, EncodeConfig::default()).await
+}
+
+pub async fn encode_to_bytes_with<T>(
+    vc: &DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>,
+    config: EncodeConfig,
+) -> Vec<u8>
+where
+    T: OpticalBarcodeCredentialSubject,
+{
+    let mut bytes = Vec::new();
+    encode_to_writer_with(vc, config, &mut bytes).await.unwrap();
+    bytes
+}
+
+/// Encodes the given credential into CBOR-LD bytes, checking that the bytes
+/// follow the CBOR core deterministic encoding rules.
+///
+/// The bytes are decoded, then re-encoded with map keys sorted by their
+/// encoded bytes and integers in their shortest form, as required by
+/// [RFC 8949 section 4.2.1]. Fails with [`io::ErrorKind::InvalidData`] if this
+/// does not produce the exact same bytes.
+///
+/// [RFC 8949 section 4.2.1]: <https://www.rfc-editor.org/rfc/rfc8949#section-4.2.1>
+pub async fn encode_to_bytes_checked<T>(
+    vc: &DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>,
+) -> io::Result<Vec<u8>>
+where
+    T: OpticalBarcodeCredentialSubject,
+{
+    let bytes = encode_to_bytes(vc).await;
+
+    let mut value: CborValue = ciborium::from_reader(bytes.as_slice())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    sort_map_keys(&mut value);
+
+    let mut reencoded = Vec::with_capacity(bytes.len());
+    ciborium::into_writer(&value, &mut reencoded)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+
+    if reencoded != bytes {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "non-canonical CBOR-LD encoding",
+        ));
+    }
+
+    Ok(bytes)
+}
+
+/// Recursively sorts map entries by the bytewise lexicographic order of their
+/// encoded keys.
+fn sort_map_keys(value: &mut CborValue) {
+    match value {
+        CborValue::Array(items) => items.iter_mut().for_each(sort_map_keys),
+        CborValue::Map(entries) => {
+            for (key, value) in entries.iter_mut() {
+                sort_map_keys(key);
+                sort_map_keys(value);
+            }
+
+            entries.sort_by_cached_key(|(key, _)| {
+                let mut bytes = Vec::new();
+                // Writing a CBOR value into a `Vec` cannot fail.
+                ciborium::into_writer(key, &mut bytes).unwrap();
+                bytes
+            })
+        }
+        CborValue::Tag(_, value) => sort_map_keys(value),
+        _ => (),
+    }
+}
+
+/// Size of a credential before and after CBOR-LD compression.
+///
+/// See [`compression_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CompressionStats {
+    /// Length of the compact JSON-LD serialization, in bytes.
+    pub json_len: usize,
+
+    /// Length of the CBOR-LD encoding, in bytes, as returned by
+    /// [`encode_to_bytes`].
+    pub cbor_ld_len: usize,
+}
+
+impl CompressionStats {
+    /// Ratio of the CBOR-LD length to the JSON-LD length. The lower, the
+    /// better the compression.
+    pub fn ratio(&self) -> f64 {
+        self.cbor_ld_len as f64 / self.json_len as f64
+    }
+
+    /// Number of bytes saved by the CBOR-LD compression.
+    pub fn saved(&self) -> usize {
+        self.json_len.saturating_sub(self.cbor_ld_len)
+    }
+}
+
+/// Measures how much CBOR-LD compression saves on the given credential.
+///
+/// This is a diagnostic tool for issuers deciding which optional claims to
+/// include, in order to fit a given barcode density.
+pub async fn compression_stats<T>(
+    vc: &DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>,
+) -> CompressionStats
 where
     T: OpticalBarcodeCredentialSubject,
 {
     let json = json_syntax::to_value(vc).unwrap();
-    cbor_ld::encode_to_bytes_with(&json, &*CONTEXT_LOADER, encode_options())
-        .await
-        .unwrap()
+    CompressionStats {
+        json_len: json.compact_print().to_string().len(),
+        cbor_ld_len: encode_to_bytes(vc).await.len(),
+    }
+}
+
+/// Encodes the given credential into `writer`.
+///
+/// The credential is first compressed into an in-memory CBOR value (see
+/// [`encode`]), which is then serialized into `writer`. Only the output bytes
+/// are not buffered.
+pub async fn encode_to_writer<T, W: io::Write>(
+    vc: &DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>,
+    writer: W,
+) -> io::Result<()>
+where
+    T: OpticalBarcodeCredentialSubject,
+{
+    encode_to_writer_with(vc, EncodeConfig::default(), writer).await
+}
+
+/// Encodes the given credential into `writer`, using the given
+/// configuration.
+///
+/// See [`encode_to_writer`].
+pub async fn encode_to_writer_with<T, W: io::Write>(
+    vc: &DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>,
+    config: EncodeConfig,
+    writer: W,
+) -> io::Result<()>
+where
+    T: OpticalBarcodeCredentialSubject,
+{
+    let cbor = encode_with(vc, config).await;
+    ciborium::into_writer(&cbor, writer).map_err(|e| match e {
+        ciborium::ser::Error::Io(e) => e,
+        ciborium::ser::Error::Value(msg) => io::Error::new(io::ErrorKind::InvalidData, msg),
+    })
 }
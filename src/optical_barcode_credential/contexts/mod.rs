@@ -35,6 +35,16 @@ lazy_static! {
     };
 }
 
+/// Lists the IRIs of the contexts bundled in [`CONTEXT_LOADER`], in
+/// lexicographic order.
+///
+/// Any other context must be provided by a custom loader.
+pub fn builtin_context_iris() -> Vec<&'static Iri> {
+    let mut iris: Vec<&'static Iri> = CONTEXT_LOADER.keys().map(IriBuf::as_iri).collect();
+    iris.sort_unstable();
+    iris
+}
+
 fn load(json: &str) -> RemoteDocument {
     RemoteDocument::new(None, None, Value::parse_str(json).unwrap().0)
 }
@@ -56,3 +66,14 @@ pub struct VcBarcodesV1;
 impl RequiredContext for VcBarcodesV1 {
     const CONTEXT_IRI: &'static Iri = iri!("https://w3id.org/vc-barcodes/v1");
 }
+
+/// Example jurisdiction context used by the specification's worked examples.
+///
+/// It only defines the terms of the example issuer, its verification method
+/// and its status list, so that the test vectors can be compressed. Real
+/// issuers should provide their own context instead.
+pub struct UtopiaV2;
+
+impl RequiredContext for UtopiaV2 {
+    const CONTEXT_IRI: &'static Iri = iri!("https://w3id.org/utopia/v2");
+}
@@ -0,0 +1,65 @@
+use json_syntax::{Object, Print, Value};
+use serde::Serialize;
+
+use super::{
+    OpticalBarcodeCredential, OpticalBarcodeCredentialSubject, VerifiableOpticalBarcodeCredential,
+};
+
+/// Semantic equality of optical barcode credentials.
+///
+/// Unlike comparing serialized forms, this ignores the order of object
+/// entries and of `@context` entries. Other arrays (such as the credential
+/// subjects) are compared in order.
+pub trait SemanticEq {
+    fn semantically_eq(&self, other: &Self) -> bool;
+}
+
+impl<T: OpticalBarcodeCredentialSubject> SemanticEq for OpticalBarcodeCredential<T> {
+    fn semantically_eq(&self, other: &Self) -> bool {
+        normalized_eq(self, other)
+    }
+}
+
+impl<T: OpticalBarcodeCredentialSubject> SemanticEq for VerifiableOpticalBarcodeCredential<T> {
+    fn semantically_eq(&self, other: &Self) -> bool {
+        normalized_eq(self, other)
+    }
+}
+
+fn normalized_eq<T: Serialize>(a: &T, b: &T) -> bool {
+    match (json_syntax::to_value(a), json_syntax::to_value(b)) {
+        (Ok(mut a), Ok(mut b)) => {
+            normalize(&mut a, false);
+            normalize(&mut b, false);
+            a == b
+        }
+        _ => false,
+    }
+}
+
+/// Sorts object entries by key, and `@context` entries by value.
+fn normalize(value: &mut Value, is_context: bool) {
+    match value {
+        Value::Array(items) => {
+            for item in items.iter_mut() {
+                normalize(item, false)
+            }
+
+            if is_context {
+                items.sort_by_cached_key(|item| item.compact_print().to_string())
+            }
+        }
+        Value::Object(object) => {
+            let mut entries: Vec<_> = std::mem::replace(object, Object::new())
+                .into_iter()
+                .collect();
+            entries.sort_by(|a, b| a.key.cmp(&b.key));
+
+            for mut entry in entries {
+                normalize(&mut entry.value, entry.key.as_str() == "@context");
+                object.push(entry.key, entry.value);
+            }
+        }
+        _ => (),
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{de::DeserializeOwned, Serialize};
+use sha2::{Digest, Sha256};
 use ssi::claims::{
     data_integrity::DataIntegrity,
     vc::{
@@ -8,7 +9,8 @@ use ssi::claims::{
 };
 
 use crate::{
-    ecdsa_xi_2023::EcdsaXi2023, terse_bitstring_status_list_entry::TerseBitstringStatusListEntry,
+    aamva::dlid::DlMandatoryElement, ecdsa_xi_2023::EcdsaXi2023,
+    terse_bitstring_status_list_entry::TerseBitstringStatusListEntry,
 };
 
 mod contexts;
@@ -19,6 +21,16 @@ mod verification;
 pub use verification::*;
 mod compression;
 pub use compression::*;
+mod validation;
+pub use validation::*;
+mod equality;
+pub use equality::*;
+mod proof_info;
+pub use proof_info::*;
+mod summary;
+pub use summary::*;
+mod portrait;
+pub use portrait::*;
 
 /// Optical barcode credential.
 ///
@@ -45,11 +57,34 @@ impl RequiredType for OpticalBarcodeCredentialType {
 /// # Safety
 ///
 /// This must be either
-///   - [`AamvaDriversLicenseScannableInformation`], or
-///   - [`MachineReadableZone`].
+///   - [`AamvaDriversLicenseScannableInformation`],
+///   - [`MachineReadableZone`], or
+///   - a [`WithPortraitHash`] of one of those.
 pub unsafe trait OpticalBarcodeCredentialSubject: Serialize + DeserializeOwned {
-    // type Context: RequiredContext;
     type ExtraInformation: ?Sized;
 
-    fn create_optical_data(&self, xi: &Self::ExtraInformation) -> [u8; 32];
+    /// Credential subject `type`.
+    const TYPE: &'static str;
+
+    /// Returns the canonical optical data bytes, before hashing.
+    ///
+    /// This is mostly useful to debug canonicalization mismatches.
+    fn create_canonical_optical_data(&self, xi: &Self::ExtraInformation) -> Vec<u8>;
+
+    /// Returns the optical data, the SHA-256 digest of the
+    /// [canonical optical data](Self::create_canonical_optical_data).
+    ///
+    /// The VC Barcodes specification always uses SHA-256 here, whatever the
+    /// issuer key curve. The `ecdsa-xi-2023` cryptosuite then hashes these 32
+    /// bytes again, as its `extraInformation`, with the hash function of the
+    /// curve (SHA-384 for P-384 keys).
+    fn create_optical_data(&self, xi: &Self::ExtraInformation) -> [u8; 32] {
+        Sha256::digest(self.create_canonical_optical_data(xi)).into()
+    }
+
+    /// Returns the DL elements protected by the signature, if this is an
+    /// AAMVA credential subject with a valid protected component index.
+    fn protected_elements(&self) -> Option<Vec<DlMandatoryElement>> {
+        None
+    }
 }
@@ -0,0 +1,139 @@
+use iref::UriBuf;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use ssi::{
+    claims::{data_integrity::ProofOptions, ProofValidationError, Verification},
+    crypto::algorithm::ES256OrES384,
+    verification_methods::{MessageSigner, Multikey, Signer, VerificationMethodResolver},
+};
+
+use crate::{
+    aamva::dlid::DlMandatoryElement, terse_bitstring_status_list_entry::TerseStatusListProvider,
+};
+
+use super::{
+    create_from_optical_data, verify_from_optical_data, OpticalBarcodeCredentialSubject, SignError,
+    SignatureParameters, VerifiableOpticalBarcodeCredential, VerificationParameters,
+};
+
+/// Credential subject whose optical data is also bound to the cardholder
+/// portrait.
+///
+/// The optical data is computed from the canonical optical data of the inner
+/// subject, followed by the SHA-256 digest of the portrait image. Replacing
+/// the portrait on the physical document hence invalidates the credential.
+///
+/// The subject is serialized exactly as the inner subject: nothing in the
+/// credential tells that a portrait is bound to it. Verifiers must know it
+/// beforehand, and use [`verify_with_portrait`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct WithPortraitHash<T>(pub T);
+
+impl<T: OpticalBarcodeCredentialSubject> WithPortraitHash<T> {
+    /// Returns the canonical optical data bytes, before hashing, binding the
+    /// given portrait digest.
+    pub fn create_canonical_optical_data_with_portrait(
+        &self,
+        xi: &T::ExtraInformation,
+        portrait_hash: &[u8; 32],
+    ) -> Vec<u8> {
+        let mut canonical_data = self.0.create_canonical_optical_data(xi);
+        canonical_data.extend(portrait_hash);
+        canonical_data
+    }
+
+    /// Returns the optical data binding the given portrait digest.
+    pub fn create_optical_data_with_portrait(
+        &self,
+        xi: &T::ExtraInformation,
+        portrait_hash: &[u8; 32],
+    ) -> [u8; 32] {
+        Sha256::digest(self.create_canonical_optical_data_with_portrait(xi, portrait_hash)).into()
+    }
+}
+
+/// [`WithPortraitHash`] extra information: the inner subject extra
+/// information, and the SHA-256 digest of the portrait image.
+#[derive(Debug, Clone)]
+pub struct PortraitBinding<X> {
+    pub extra_information: X,
+    pub portrait_hash: [u8; 32],
+}
+
+impl<X> PortraitBinding<X> {
+    /// Binds the given extra information to the portrait image, hashing it.
+    pub fn new(extra_information: X, portrait: &[u8]) -> Self {
+        Self {
+            extra_information,
+            portrait_hash: Sha256::digest(portrait).into(),
+        }
+    }
+}
+
+unsafe impl<T> OpticalBarcodeCredentialSubject for WithPortraitHash<T>
+where
+    T: OpticalBarcodeCredentialSubject,
+    T::ExtraInformation: Sized,
+{
+    type ExtraInformation = PortraitBinding<T::ExtraInformation>;
+
+    const TYPE: &'static str = T::TYPE;
+
+    fn create_canonical_optical_data(&self, xi: &Self::ExtraInformation) -> Vec<u8> {
+        self.create_canonical_optical_data_with_portrait(&xi.extra_information, &xi.portrait_hash)
+    }
+
+    fn protected_elements(&self) -> Option<Vec<DlMandatoryElement>> {
+        self.0.protected_elements()
+    }
+}
+
+/// Creates a new optical barcode credential, bound to the given portrait
+/// image.
+///
+/// See [`WithPortraitHash`].
+pub async fn create_with_portrait<T, R, S>(
+    extra_information: &T::ExtraInformation,
+    portrait: &[u8],
+    issuer: UriBuf,
+    credential_subject: T,
+    options: ProofOptions<Multikey, ()>,
+    params: SignatureParameters<R, S>,
+) -> Result<VerifiableOpticalBarcodeCredential<WithPortraitHash<T>>, SignError>
+where
+    T: OpticalBarcodeCredentialSubject,
+    T::ExtraInformation: Sized,
+    R: VerificationMethodResolver<Method = Multikey>,
+    S: Signer<Multikey>,
+    S::MessageSigner: MessageSigner<ES256OrES384>,
+{
+    let subject = WithPortraitHash(credential_subject);
+    let optical_data = subject
+        .create_optical_data_with_portrait(extra_information, &Sha256::digest(portrait).into());
+    create_from_optical_data(&optical_data, issuer, subject, options, params).await
+}
+
+/// Verifies an optical barcode credential bound to the given portrait image.
+///
+/// Fails if the portrait differs from the one the credential was issued for.
+/// See [`WithPortraitHash`].
+pub async fn verify_with_portrait<T, R, C>(
+    vc: &VerifiableOpticalBarcodeCredential<WithPortraitHash<T>>,
+    extra_information: &T::ExtraInformation,
+    portrait: &[u8],
+    params: VerificationParameters<R, C>,
+) -> Result<Verification, ProofValidationError>
+where
+    T: OpticalBarcodeCredentialSubject,
+    T::ExtraInformation: Sized,
+    R: VerificationMethodResolver<Method = Multikey>,
+    C: TerseStatusListProvider,
+{
+    let optical_data = vc
+        .credential_subjects
+        .first()
+        .unwrap()
+        .create_optical_data_with_portrait(extra_information, &Sha256::digest(portrait).into());
+    verify_from_optical_data(vc, &optical_data, params).await
+}
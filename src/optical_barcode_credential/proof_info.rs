@@ -0,0 +1,21 @@
+use iref::Iri;
+
+use super::{OpticalBarcodeCredentialSubject, VerifiableOpticalBarcodeCredential};
+
+/// Proof information accessors.
+pub trait ProofInfo {
+    /// Returns the identifier of the verification method (issuer key) of the
+    /// first proof, if any.
+    ///
+    /// Useful to display the issuer key, or to pre-resolve it before
+    /// verification.
+    fn verification_method_id(&self) -> Option<&Iri>;
+}
+
+impl<T: OpticalBarcodeCredentialSubject> ProofInfo for VerifiableOpticalBarcodeCredential<T> {
+    fn verification_method_id(&self) -> Option<&Iri> {
+        self.proofs
+            .first()
+            .map(|proof| proof.verification_method.id())
+    }
+}
@@ -11,17 +11,42 @@ use ssi::{
 };
 
 use crate::{
-    ecdsa_xi_2023::{EcdsaXi2023, ExtraInformation},
+    ecdsa_xi_2023::{Curve, EcdsaXi2023, ExtraInformation, UnsupportedKey},
     terse_bitstring_status_list_entry::TerseBitstringStatusListEntry,
+    DateTime, Utc,
 };
 
-use super::{OpticalBarcodeCredential, OpticalBarcodeCredentialSubject, CONTEXT_LOADER};
+use super::{
+    OpticalBarcodeCredential, OpticalBarcodeCredentialSubject, SingleKeyResolver, CONTEXT_LOADER,
+};
 
 /// Optical barcode credential signature parameters.
 pub struct SignatureParameters<R, S> {
     pub resolver: R,
+
+    /// Signer.
+    ///
+    /// Signing is fully asynchronous: the signer does not need access to the
+    /// private key and may call out to a remote KMS or HSM. See the
+    /// `kms_sign` example.
     pub signer: S,
     pub status: Option<Status>,
+
+    /// Proof creation date, overriding the one set in the proof options.
+    ///
+    /// Useful to produce reproducible signatures.
+    pub created: Option<DateTime<Utc>>,
+
+    /// Proof domain, overriding the one set in the proof options.
+    pub domain: Option<String>,
+
+    /// Proof challenge, overriding the one set in the proof options.
+    pub challenge: Option<String>,
+
+    /// Proof expiration date, overriding the one set in the proof options.
+    ///
+    /// Verification rejects proofs past this date.
+    pub expires: Option<DateTime<Utc>>,
 }
 
 impl<R, S> SignatureParameters<R, S> {
@@ -30,8 +55,36 @@ impl<R, S> SignatureParameters<R, S> {
             resolver,
             signer,
             status,
+            created: None,
+            domain: None,
+            challenge: None,
+            expires: None,
         }
     }
+
+    /// Sets the proof creation date.
+    pub fn with_created(mut self, created: DateTime<Utc>) -> Self {
+        self.created = Some(created);
+        self
+    }
+
+    /// Sets the proof domain.
+    pub fn with_domain(mut self, domain: String) -> Self {
+        self.domain = Some(domain);
+        self
+    }
+
+    /// Sets the proof challenge.
+    pub fn with_challenge(mut self, challenge: String) -> Self {
+        self.challenge = Some(challenge);
+        self
+    }
+
+    /// Sets the proof expiration date.
+    pub fn with_proof_expiry(mut self, expires: DateTime<Utc>) -> Self {
+        self.expires = Some(expires);
+        self
+    }
 }
 
 /// Creates a new optical barcode credential.
@@ -43,7 +96,7 @@ pub async fn create<T, R, S>(
     credential_subject: T,
     options: ProofOptions<ssi::verification_methods::Multikey, ()>,
     params: SignatureParameters<R, S>,
-) -> Result<DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>, SignatureError>
+) -> Result<DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>, SignError>
 where
     T: OpticalBarcodeCredentialSubject,
     R: VerificationMethodResolver<Method = Multikey>,
@@ -63,7 +116,7 @@ pub async fn create_from_optical_data<T, R, S>(
     credential_subject: T,
     options: ProofOptions<ssi::verification_methods::Multikey, ()>,
     params: SignatureParameters<R, S>,
-) -> Result<DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>, SignatureError>
+) -> Result<DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>, SignError>
 where
     T: OpticalBarcodeCredentialSubject,
     R: VerificationMethodResolver<Method = Multikey>,
@@ -76,12 +129,17 @@ where
     sign_from_optical_data(unsigned, optical_data, options, params).await
 }
 
+/// Signs the given credential, binding it to the extra information.
+///
+/// If `params.status` is set, the corresponding status entry is appended to
+/// the credential `credentialStatus` before signing. To sign a credential
+/// whose status entries are already attached, use [`sign_prepared`].
 pub async fn sign<'a, T, R, S>(
     unsigned: OpticalBarcodeCredential<T>,
     extra_information: &T::ExtraInformation,
     options: ProofOptions<ssi::verification_methods::Multikey, ()>,
     params: SignatureParameters<R, S>,
-) -> Result<DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>, SignatureError>
+) -> Result<DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>, SignError>
 where
     T: OpticalBarcodeCredentialSubject,
     R: VerificationMethodResolver<Method = Multikey>,
@@ -96,12 +154,93 @@ where
     sign_from_optical_data(unsigned, &optical_data, options, params).await
 }
 
+/// Signs the given credential exactly as it is, binding it to the extra
+/// information.
+///
+/// Unlike [`sign`], the credential `credentialStatus` is left untouched:
+/// status management is entirely up to the caller. Use this when the
+/// credential was fully assembled beforehand, status entries included. Fails
+/// if `params.status` is set, since it would be ignored.
+pub async fn sign_prepared<T, R, S>(
+    credential: OpticalBarcodeCredential<T>,
+    extra_information: &T::ExtraInformation,
+    options: ProofOptions<ssi::verification_methods::Multikey, ()>,
+    params: SignatureParameters<R, S>,
+) -> Result<DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>, SignError>
+where
+    T: OpticalBarcodeCredentialSubject,
+    R: VerificationMethodResolver<Method = Multikey>,
+    S: Signer<Multikey>,
+    S::MessageSigner: MessageSigner<ES256OrES384>,
+{
+    if params.status.is_some() {
+        return Err(SignatureError::other(
+            "status parameter is not supported when signing a prepared credential",
+        )
+        .into());
+    }
+
+    let optical_data = credential
+        .credential_subjects
+        .first()
+        .unwrap()
+        .create_optical_data(extra_information);
+    let extra_information = ExtraInformation::OpticalData(optical_data.to_vec());
+    sign_with_extra_information(credential, extra_information, options, params).await
+}
+
 pub async fn sign_from_optical_data<T, R, S>(
-    mut unsigned: OpticalBarcodeCredential<T>,
+    unsigned: OpticalBarcodeCredential<T>,
     optical_data: impl Into<Vec<u8>>,
     options: ProofOptions<ssi::verification_methods::Multikey, ()>,
     params: SignatureParameters<R, S>,
-) -> Result<DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>, SignatureError>
+) -> Result<DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>, SignError>
+where
+    T: OpticalBarcodeCredentialSubject,
+    R: VerificationMethodResolver<Method = Multikey>,
+    S: Signer<Multikey>,
+    S::MessageSigner: MessageSigner<ES256OrES384>,
+{
+    let extra_information = ExtraInformation::OpticalData(optical_data.into());
+    sign_with_extra_information(unsigned, extra_information, options, params).await
+}
+
+/// Signs a credential over the digest of its optical data, instead of the
+/// optical data itself.
+///
+/// The digest must be computed with the hash function of the issuer key
+/// curve: SHA-256 for P-256 keys, SHA-384 for P-384 keys. The resulting proof
+/// is identical to the one [`sign_from_optical_data`] would produce from the
+/// optical data, and is verified the same way.
+///
+/// # Security
+///
+/// The digest is signed as is. The credential is only bound to the physical
+/// document if the digest was actually computed over its optical data: the
+/// component computing it (e.g. a secure element reading the document) must
+/// be trusted as much as the signing key itself.
+pub async fn sign_from_optical_data_hash<T, R, S>(
+    unsigned: OpticalBarcodeCredential<T>,
+    optical_data_hash: impl Into<Vec<u8>>,
+    options: ProofOptions<ssi::verification_methods::Multikey, ()>,
+    params: SignatureParameters<R, S>,
+) -> Result<DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>, SignError>
+where
+    T: OpticalBarcodeCredentialSubject,
+    R: VerificationMethodResolver<Method = Multikey>,
+    S: Signer<Multikey>,
+    S::MessageSigner: MessageSigner<ES256OrES384>,
+{
+    let extra_information = ExtraInformation::OpticalDataHash(optical_data_hash.into());
+    sign_with_extra_information(unsigned, extra_information, options, params).await
+}
+
+async fn sign_with_extra_information<T, R, S>(
+    mut unsigned: OpticalBarcodeCredential<T>,
+    extra_information: ExtraInformation,
+    mut options: ProofOptions<ssi::verification_methods::Multikey, ()>,
+    params: SignatureParameters<R, S>,
+) -> Result<DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>, SignError>
 where
     T: OpticalBarcodeCredentialSubject,
     R: VerificationMethodResolver<Method = Multikey>,
@@ -118,16 +257,66 @@ where
         )
     }
 
+    if let Some(created) = params.created {
+        options.created = Some(created.into());
+    }
+
+    if let Some(domain) = params.domain {
+        options.domains = vec![domain];
+    }
+
+    if let Some(challenge) = params.challenge {
+        options.challenge = Some(challenge);
+    }
+
+    if let Some(expires) = params.expires {
+        options.expires = Some(expires.into());
+    }
+
+    let method = params
+        .resolver
+        .resolve_verification_method(
+            None,
+            options.verification_method.as_ref().map(|m| m.borrowed()),
+        )
+        .await
+        .map_err(SignatureError::other)?;
+    let curve = Curve::of(&method)?;
+
+    if let ExtraInformation::OpticalDataHash(hash) = &extra_information {
+        if hash.len() != curve.digest_len() {
+            return Err(SignatureError::other(format!(
+                "invalid optical data digest length {} (expected {} for {curve} keys)",
+                hash.len(),
+                curve.digest_len()
+            ))
+            .into());
+        }
+    }
+
+    // The method is already resolved: do not resolve it again.
     EcdsaXi2023
         .sign_with(
             XiSignatureEnvironment(&*CONTEXT_LOADER),
             unsigned,
-            params.resolver,
+            SingleKeyResolver(&method),
             params.signer,
             options,
-            ExtraInformation(optical_data.into()),
+            extra_information,
         )
         .await
+        .map_err(Into::into)
+}
+
+/// Optical barcode credential signature error.
+#[derive(Debug, thiserror::Error)]
+pub enum SignError {
+    /// The issuer key cannot be used with the `ecdsa-xi-2023` cryptosuite.
+    #[error(transparent)]
+    UnsupportedKey(#[from] UnsupportedKey),
+
+    #[error(transparent)]
+    Signature(#[from] SignatureError),
 }
 
 struct XiSignatureEnvironment<'a, L>(&'a L);
@@ -147,17 +336,27 @@ pub struct Status {
 
 #[cfg(test)]
 mod tests {
+    use std::borrow::Cow;
+
+    use iref::{Iri, IriBuf};
+    use json_syntax::Parse;
     use ssi::{
         claims::data_integrity::ProofOptions,
         dids::{AnyDidMethod, DIDKey, DIDResolver},
-        verification_methods::SingleSecretSigner,
+        verification_methods::{
+            Multikey, ReferenceOrOwnedRef, ResolutionOptions, SingleSecretSigner,
+            VerificationMethodResolutionError, VerificationMethodResolver,
+        },
         JWK,
     };
     use static_iref::uri;
 
-    use crate::{create, MachineReadableZone, MRZ};
+    use crate::{
+        create, ecdsa_xi_2023::UnsupportedKey, optical_barcode_credential::VerificationParameters,
+        verify, MachineReadableZone, MRZ,
+    };
 
-    use super::SignatureParameters;
+    use super::{SignError, SignatureParameters};
 
     fn assert_send(_: impl Send) {}
 
@@ -188,4 +387,135 @@ mod tests {
             params,
         ))
     }
+
+    #[async_std::test]
+    async fn create_only_uses_barcode_contexts() {
+        let jwk = JWK::generate_p256();
+
+        let vm = DIDKey::generate_url(&jwk).unwrap();
+        let options = ProofOptions::from_method(vm.into_iri().into());
+
+        let params = SignatureParameters::new(
+            AnyDidMethod::default().into_vm_resolver(),
+            SingleSecretSigner::new(jwk),
+            None,
+        );
+
+        let vc = create(
+            &MRZ_DATA,
+            uri!("http://example.org/issuer").to_owned(),
+            MachineReadableZone {},
+            options,
+            params,
+        )
+        .await
+        .unwrap();
+
+        let json = json_syntax::to_value(&vc).unwrap();
+        let context = json
+            .as_object()
+            .unwrap()
+            .get_unique("@context")
+            .unwrap()
+            .unwrap()
+            .as_array()
+            .unwrap();
+        assert_eq!(
+            context
+                .iter()
+                .map(|c| c.as_str().unwrap())
+                .collect::<Vec<_>>(),
+            [
+                "https://www.w3.org/ns/credentials/v2",
+                "https://w3id.org/vc-barcodes/v1"
+            ]
+        )
+    }
+
+    const SECP256K1_KEY: &str = "zQ3shokFTS3brHcDQrn82RUDfCZESWL1ZdCEJwekUDPQiYBme";
+
+    const SECP256K1_VM: &str = "did:key:zQ3shokFTS3brHcDQrn82RUDfCZESWL1ZdCEJwekUDPQiYBme#zQ3shokFTS3brHcDQrn82RUDfCZESWL1ZdCEJwekUDPQiYBme";
+
+    /// Resolves every verification method to the same secp256k1 key.
+    struct Secp256k1Resolver;
+
+    impl VerificationMethodResolver for Secp256k1Resolver {
+        type Method = Multikey;
+
+        async fn resolve_verification_method_with(
+            &self,
+            _issuer: Option<&Iri>,
+            _method: Option<ReferenceOrOwnedRef<'_, Multikey>>,
+            _options: ResolutionOptions,
+        ) -> Result<Cow<Multikey>, VerificationMethodResolutionError> {
+            let json = format!(
+                r#"{{"id":"{SECP256K1_VM}","type":"Multikey","controller":"did:key:{SECP256K1_KEY}","publicKeyMultibase":"{SECP256K1_KEY}"}}"#
+            );
+            let json = json_syntax::Value::parse_str(&json).unwrap().0;
+            Ok(Cow::Owned(json_syntax::from_value(json).unwrap()))
+        }
+    }
+
+    #[async_std::test]
+    async fn create_rejects_secp256k1() {
+        let vm = IriBuf::new(SECP256K1_VM.to_owned()).unwrap();
+        let options = ProofOptions::from_method(vm.into());
+
+        // The key is rejected before signing.
+        let params = SignatureParameters::new(
+            Secp256k1Resolver,
+            SingleSecretSigner::new(JWK::generate_p256()),
+            None,
+        );
+
+        let result = create(
+            &MRZ_DATA,
+            uri!("http://example.org/issuer").to_owned(),
+            MachineReadableZone {},
+            options,
+            params,
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(SignError::UnsupportedKey(UnsupportedKey::Curve(
+                "secp256k1"
+            )))
+        ))
+    }
+
+    #[async_std::test]
+    async fn create_and_verify_p384() {
+        let jwk = JWK::generate_p384();
+
+        let vm = DIDKey::generate_url(&jwk).unwrap();
+        let options = ProofOptions::from_method(vm.into_iri().into());
+
+        let params = SignatureParameters::new(
+            AnyDidMethod::default().into_vm_resolver(),
+            SingleSecretSigner::new(jwk),
+            None,
+        );
+
+        let vc = create(
+            &MRZ_DATA,
+            uri!("http://example.org/issuer").to_owned(),
+            MachineReadableZone {},
+            options,
+            params,
+        )
+        .await
+        .unwrap();
+
+        let params = VerificationParameters::new(AnyDidMethod::default().into_vm_resolver());
+        let result = verify(&vc, &MRZ_DATA, params).await.unwrap();
+        assert_eq!(result, Ok(()));
+
+        let mut other = MRZ_DATA;
+        other[2][0] = b'X';
+        let params = VerificationParameters::new(AnyDidMethod::default().into_vm_resolver());
+        let result = verify(&vc, &other, params).await.unwrap();
+        assert!(result.is_err())
+    }
 }
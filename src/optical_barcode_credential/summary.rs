@@ -0,0 +1,95 @@
+use std::fmt;
+
+use iref::{IriBuf, UriBuf};
+use ssi::claims::vc::syntax::IdOr;
+
+use crate::{aamva::dlid::DlMandatoryElement, DateTime, Utc};
+
+use super::{OpticalBarcodeCredentialSubject, ProofInfo, VerifiableOpticalBarcodeCredential};
+
+/// Human-oriented summary of an optical barcode credential.
+///
+/// Meant for CLI tools and logs. The [`Display`](fmt::Display) output lists
+/// one property per line, and is not meant to be parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CredentialSummary {
+    /// Issuer identifier.
+    pub issuer: UriBuf,
+
+    /// Credential subject type.
+    pub subject_type: &'static str,
+
+    /// Protected DL elements, for AAMVA credentials with a valid protected
+    /// component index.
+    pub protected_fields: Option<Vec<DlMandatoryElement>>,
+
+    /// Terse status list entries, as `(base_url, index)` pairs.
+    pub statuses: Vec<(UriBuf, u32)>,
+
+    /// Verification method of the first proof.
+    pub verification_method: Option<IriBuf>,
+
+    /// Creation date of the first proof.
+    pub created: Option<DateTime<Utc>>,
+}
+
+impl fmt::Display for CredentialSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "issuer: {}", self.issuer)?;
+        writeln!(f, "subject type: {}", self.subject_type)?;
+
+        if let Some(fields) = &self.protected_fields {
+            write!(f, "protected fields:")?;
+            for e in fields {
+                write!(f, " {e}")?;
+            }
+            writeln!(f)?;
+        }
+
+        for (base_url, index) in &self.statuses {
+            writeln!(f, "status: {base_url} #{index}")?;
+        }
+
+        if let Some(method) = &self.verification_method {
+            writeln!(f, "verification method: {method}")?;
+        }
+
+        match &self.created {
+            Some(created) => write!(f, "created: {}", created.to_rfc3339()),
+            None => write!(f, "created: unknown"),
+        }
+    }
+}
+
+/// Credential summary.
+pub trait Summarize {
+    fn summary(&self) -> CredentialSummary;
+}
+
+impl<T: OpticalBarcodeCredentialSubject> Summarize for VerifiableOpticalBarcodeCredential<T> {
+    fn summary(&self) -> CredentialSummary {
+        let issuer = match &self.issuer {
+            IdOr::Id(id) => id.clone(),
+            IdOr::NotId(object) => object.id.clone(),
+        };
+
+        let subject = self.credential_subjects.first();
+
+        CredentialSummary {
+            issuer,
+            subject_type: T::TYPE,
+            protected_fields: subject.and_then(T::protected_elements),
+            statuses: self
+                .credential_status
+                .iter()
+                .map(|entry| (entry.base_url.clone(), entry.index))
+                .collect(),
+            verification_method: self.verification_method_id().map(ToOwned::to_owned),
+            created: self
+                .proofs
+                .first()
+                .and_then(|proof| proof.created)
+                .map(Into::into),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use iref::Iri;
+use json_syntax::Value;
+use ssi::claims::vc::{syntax::RequiredContext, v2::V2};
+
+use super::{OpticalBarcodeCredential, OpticalBarcodeCredentialSubject, VcBarcodesV1};
+
+/// Structural validation of optical barcode credentials.
+///
+/// This is a cheap pre-flight check, not requiring any resolver or network
+/// access, that can be performed before calling [`verify`](super::verify).
+/// It does not check the credential proof.
+pub trait ValidateStructure {
+    /// Checks that the credential has the required `@context` entries, the
+    /// `OpticalBarcodeCredential` type and at least one typed credential
+    /// subject.
+    ///
+    /// The `https://www.w3.org/ns/credentials/v2` context must come first, as
+    /// required by the VC data model. The other contexts, including
+    /// `https://w3id.org/vc-barcodes/v1`, may follow in any order.
+    fn validate_structure(&self) -> Result<(), StructureError>;
+}
+
+impl<T> ValidateStructure for OpticalBarcodeCredential<T>
+where
+    T: OpticalBarcodeCredentialSubject,
+{
+    fn validate_structure(&self) -> Result<(), StructureError> {
+        let json = json_syntax::to_value(self)?;
+        validate_json_structure(&json)
+    }
+}
+
+/// Checks the structure of a JSON optical barcode credential.
+///
+/// See [`ValidateStructure::validate_structure`].
+pub fn validate_json_structure(json: &Value) -> Result<(), StructureError> {
+    let object = json.as_object().ok_or(StructureError::NotAnObject)?;
+
+    let context = string_or_strings(get(object, "@context"));
+    if context.first().copied() != Some(V2::CONTEXT_IRI.as_str()) {
+        return Err(StructureError::MissingContext(V2::CONTEXT_IRI));
+    }
+
+    if !context.contains(&VcBarcodesV1::CONTEXT_IRI.as_str()) {
+        return Err(StructureError::MissingContext(VcBarcodesV1::CONTEXT_IRI));
+    }
+
+    if !string_or_strings(get(object, "type")).contains(&"OpticalBarcodeCredential") {
+        return Err(StructureError::MissingType("OpticalBarcodeCredential"));
+    }
+
+    let subjects = match get(object, "credentialSubject") {
+        Some(Value::Array(subjects)) => subjects.iter().collect(),
+        Some(subject) => vec![subject],
+        None => Vec::new(),
+    };
+
+    if subjects.is_empty() {
+        return Err(StructureError::MissingSubject);
+    }
+
+    for subject in subjects {
+        let subject = subject.as_object().ok_or(StructureError::InvalidSubject)?;
+        if string_or_strings(get(subject, "type")).is_empty() {
+            return Err(StructureError::InvalidSubject);
+        }
+    }
+
+    Ok(())
+}
+
+fn get<'a>(object: &'a json_syntax::Object, key: &str) -> Option<&'a Value> {
+    object.get_unique(key).ok().flatten()
+}
+
+fn string_or_strings(value: Option<&Value>) -> Vec<&str> {
+    match value {
+        Some(Value::Array(items)) => items.iter().filter_map(Value::as_str).collect(),
+        Some(value) => value.as_str().into_iter().collect(),
+        None => Vec::new(),
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum StructureError {
+    #[error("credential is not a JSON object")]
+    NotAnObject,
+
+    #[error("missing `{0}` context")]
+    MissingContext(&'static Iri),
+
+    #[error("missing `{0}` type")]
+    MissingType(&'static str),
+
+    #[error("missing credential subject")]
+    MissingSubject,
+
+    #[error("invalid credential subject")]
+    InvalidSubject,
+
+    #[error(transparent)]
+    Json(#[from] json_syntax::SerializeError),
+}
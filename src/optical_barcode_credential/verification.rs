@@ -1,25 +1,74 @@
+use iref::{Iri, IriBuf};
 use ssi::{
     claims::{
-        data_integrity::DataIntegrity, DateTimeProvider, JsonLdLoaderProvider,
-        ProofValidationError, ResolverProvider, ResourceProvider, Verification,
+        data_integrity::DataIntegrity, vc::v2::Credential, DateTimeProvider, Invalid,
+        InvalidClaims, JsonLdLoaderProvider, ProofValidationError, ResolverProvider,
+        ResourceProvider, ValidateProof, Verification,
     },
     status::bitstring_status_list_20240406::StatusPurpose,
-    verification_methods::{Multikey, VerificationMethodResolver},
+    verification_methods::{
+        Multikey, ReferenceOrOwnedRef, ResolutionOptions, VerificationMethodResolutionError,
+        VerificationMethodResolver,
+    },
 };
+use std::{borrow::Cow, collections::HashMap, sync::Mutex, time::Duration};
 
 use crate::{
-    ecdsa_xi_2023::{EcdsaXi2023, ExtraInformation},
+    ecdsa_xi_2023::{Curve, EcdsaXi2023, ExtraInformation},
     terse_bitstring_status_list_entry::{NoTerseStatusListProvider, TerseStatusListProvider},
     DateTime, Utc,
 };
 
-use super::{OpticalBarcodeCredential, OpticalBarcodeCredentialSubject, CONTEXT_LOADER};
+use super::{
+    OpticalBarcodeCredential, OpticalBarcodeCredentialSubject, VerifiableOpticalBarcodeCredential,
+    CONTEXT_LOADER,
+};
 
 /// Optical barcode credential verification parameters.
 pub struct VerificationParameters<R, C = NoTerseStatusListProvider> {
     pub resolver: R,
     pub status_list_client: Option<C>,
     pub date_time: Option<DateTime<Utc>>,
+
+    /// Checks the credential `validFrom`/`validUntil` bounds against
+    /// `date_time` (or the current time), failing with `"not yet valid"` or
+    /// `"expired"`.
+    ///
+    /// This is the ssi [`Credential::validate_credential`] check, performed
+    /// before the proof so that it fails with a dedicated error. When
+    /// disabled, ssi's own claims validation is skipped too, but the proofs
+    /// are still checked at `date_time`.
+    ///
+    /// Enabled by default.
+    pub require_valid_period: bool,
+
+    /// Skips the credential status entries, verifying the signature (and
+    /// validity period) only.
+    ///
+    /// This is meant for offline scanning, where status lists cannot be
+    /// fetched. A revoked or suspended credential then verifies successfully,
+    /// so the result must not be trusted beyond the authenticity of the
+    /// credential.
+    ///
+    /// Disabled by default.
+    pub ignore_status: bool,
+
+    /// Accepts AAMVA credentials whose protected component index is empty.
+    ///
+    /// Such a credential signs no DL element at all, so it verifies against
+    /// any PDF417 data: it is rejected by default, with the
+    /// `"no protected component"` error.
+    ///
+    /// Disabled by default.
+    pub allow_empty_protected_set: bool,
+
+    /// Minimum key curve accepted for the proof verification method.
+    ///
+    /// The verification method is resolved and its curve checked before the
+    /// signature, failing with the `"key curve below policy"` error.
+    ///
+    /// Any curve supported by `ecdsa-xi-2023` is accepted by default.
+    pub min_curve: Option<Curve>,
 }
 
 impl<R> VerificationParameters<R> {
@@ -28,6 +77,10 @@ impl<R> VerificationParameters<R> {
             resolver,
             status_list_client: None,
             date_time: None,
+            require_valid_period: true,
+            ignore_status: false,
+            allow_empty_protected_set: false,
+            min_curve: None,
         }
     }
 }
@@ -38,8 +91,44 @@ impl<R, C> VerificationParameters<R, C> {
             resolver,
             status_list_client: Some(status_list_client),
             date_time: None,
+            require_valid_period: true,
+            ignore_status: false,
+            allow_empty_protected_set: false,
+            min_curve: None,
         }
     }
+
+    /// Enables or disables the credential validity period check.
+    pub fn require_valid_period(mut self, value: bool) -> Self {
+        self.require_valid_period = value;
+        self
+    }
+
+    /// Enables or disables status checks.
+    ///
+    /// See [`VerificationParameters::ignore_status`] for the security
+    /// trade-off.
+    pub fn ignore_status(mut self, value: bool) -> Self {
+        self.ignore_status = value;
+        self
+    }
+
+    /// Accepts or rejects credentials with an empty protected component
+    /// index.
+    ///
+    /// See [`VerificationParameters::allow_empty_protected_set`].
+    pub fn allow_empty_protected_set(mut self, value: bool) -> Self {
+        self.allow_empty_protected_set = value;
+        self
+    }
+
+    /// Rejects verification methods whose key curve is below `curve`.
+    ///
+    /// See [`VerificationParameters::min_curve`].
+    pub fn require_min_curve(mut self, curve: Curve) -> Self {
+        self.min_curve = Some(curve);
+        self
+    }
 }
 
 pub async fn verify<T, R, C>(
@@ -60,6 +149,46 @@ where
     verify_from_optical_data(vc, &optical_data, params).await
 }
 
+/// Synchronously verifies an optical barcode credential.
+///
+/// Runs [`verify`] to completion, blocking the current thread, without
+/// requiring an async runtime. The built-in JSON-LD contexts are loaded from
+/// memory, so this is suitable for offline verification as long as the
+/// `resolver` and status list client do not perform actual asynchronous I/O,
+/// such as [`DidKeyResolver`](crate::DidKeyResolver).
+///
+/// This must not be called from an async task, nor from a thread that is not
+/// allowed to block, such as the main thread of a web browser.
+#[cfg(feature = "blocking")]
+pub fn verify_blocking<T, R, C>(
+    vc: &DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>,
+    extra_information: &T::ExtraInformation,
+    params: VerificationParameters<R, C>,
+) -> Result<Verification, ProofValidationError>
+where
+    T: OpticalBarcodeCredentialSubject,
+    R: VerificationMethodResolver<Method = Multikey>,
+    C: TerseStatusListProvider,
+{
+    pollster::block_on(verify(vc, extra_information, params))
+}
+
+/// Verifies an optical barcode credential against the given optical data.
+///
+/// AAMVA credentials protecting no DL element are rejected upfront (see
+/// [`VerificationParameters::allow_empty_protected_set`]). Status list
+/// entries are checked next, so a revoked or suspended
+/// credential is reported as such even if it is also outside of its validity
+/// period. The validity period is then checked (unless disabled with
+/// [`VerificationParameters::require_valid_period`]), followed by the proof
+/// `expires` date and the verification method key curve (see
+/// [`VerificationParameters::require_min_curve`]), before the proof itself.
+///
+/// Status entries require a status list client, unless they are skipped with
+/// [`VerificationParameters::ignore_status`].
+///
+/// Statuses with the `message` purpose never fail verification. Use
+/// [`verify_from_optical_data_with_status`] to retrieve them.
 pub async fn verify_from_optical_data<T, R, C>(
     vc: &DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>,
     optical_data: impl Into<Vec<u8>>,
@@ -70,48 +199,730 @@ where
     R: VerificationMethodResolver<Method = Multikey>,
     C: TerseStatusListProvider,
 {
-    for terse_entry in &vc.credential_status {
-        let client = params
-            .status_list_client
-            .as_ref()
-            .ok_or_else(|| ProofValidationError::other("no status list parameters"))?;
+    verify_from_optical_data_with_status(vc, optical_data, params)
+        .await
+        .map(|(verification, _)| verification)
+}
 
-        // let entry = terse_entry
-        //     .to_bitstring_status_list_entry(status_params.list_len, status_params.status_purpose);
+/// Status value of a credential status entry with the `message` purpose.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StatusMessage {
+    /// Index of the entry in the credential `credentialStatus` list.
+    pub entry_index: usize,
 
-        let (status_purpose, status) = client
-            .get_status(terse_entry)
-            .await
-            .map_err(ProofValidationError::other)?;
+    /// Status value.
+    pub status: u8,
+}
+
+/// Verifies an optical barcode credential, also returning the statuses
+/// with the `message` purpose.
+///
+/// See [`verify`] for more information.
+pub async fn verify_with_status<T, R, C>(
+    vc: &DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>,
+    extra_information: &T::ExtraInformation,
+    params: VerificationParameters<R, C>,
+) -> Result<(Verification, Vec<StatusMessage>), ProofValidationError>
+where
+    T: OpticalBarcodeCredentialSubject,
+    R: VerificationMethodResolver<Method = Multikey>,
+    C: TerseStatusListProvider,
+{
+    let optical_data = vc
+        .credential_subjects
+        .first()
+        .unwrap()
+        .create_optical_data(extra_information);
+    verify_from_optical_data_with_status(vc, &optical_data, params).await
+}
+
+/// Verifies an optical barcode credential against the given optical data,
+/// also returning the statuses with the `message` purpose.
+///
+/// See [`verify_from_optical_data`] for more information.
+pub async fn verify_from_optical_data_with_status<T, R, C>(
+    vc: &DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>,
+    optical_data: impl Into<Vec<u8>>,
+    params: VerificationParameters<R, C>,
+) -> Result<(Verification, Vec<StatusMessage>), ProofValidationError>
+where
+    T: OpticalBarcodeCredentialSubject,
+    R: VerificationMethodResolver<Method = Multikey>,
+    C: TerseStatusListProvider,
+{
+    let extra_information = ExtraInformation::OpticalData(optical_data.into());
+    verify_with_extra_information(vc, extra_information, params).await
+}
+
+/// Verifies an optical barcode credential against the digest of its optical
+/// data, instead of the optical data itself.
+///
+/// The digest must be computed with the hash function of the issuer key
+/// curve, as for
+/// [`sign_from_optical_data_hash`](super::sign_from_optical_data_hash). The
+/// same checks as [`verify_from_optical_data`] are performed.
+///
+/// # Security
+///
+/// The credential is only checked against the physical document if the
+/// digest was actually computed over its optical data. The component
+/// computing it must be trusted: replaying the digest of a genuine document
+/// makes its credential verify whatever document is presented.
+pub async fn verify_from_optical_data_hash<T, R, C>(
+    vc: &DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>,
+    optical_data_hash: impl Into<Vec<u8>>,
+    params: VerificationParameters<R, C>,
+) -> Result<Verification, ProofValidationError>
+where
+    T: OpticalBarcodeCredentialSubject,
+    R: VerificationMethodResolver<Method = Multikey>,
+    C: TerseStatusListProvider,
+{
+    let extra_information = ExtraInformation::OpticalDataHash(optical_data_hash.into());
+    verify_with_extra_information(vc, extra_information, params)
+        .await
+        .map(|(verification, _)| verification)
+}
+
+async fn verify_with_extra_information<T, R, C>(
+    vc: &DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>,
+    extra_information: ExtraInformation,
+    params: VerificationParameters<R, C>,
+) -> Result<(Verification, Vec<StatusMessage>), ProofValidationError>
+where
+    T: OpticalBarcodeCredentialSubject,
+    R: VerificationMethodResolver<Method = Multikey>,
+    C: TerseStatusListProvider,
+{
+    let messages = check_status_and_validity_period(vc, &params, None)
+        .await
+        .map_err(CheckFailure::into_error)?;
+
+    let cache = Mutex::new(HashMap::new());
+    let resolver = CachingResolver {
+        resolver: &params.resolver,
+        cache: &cache,
+    };
+
+    check_key_policy(vc, &resolver, params.min_curve)
+        .await?
+        .map_err(CheckFailure::into_error)?;
+
+    let verification = verify_proof(
+        vc,
+        extra_information,
+        resolver,
+        params.date_time,
+        params.require_valid_period,
+    )
+    .await?;
+    Ok((verification, messages))
+}
+
+/// Detailed verification outcome.
+///
+/// See [`verify_detailed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VerificationOutcome {
+    /// The credential is valid.
+    Valid,
+
+    /// A status list entry with the `revocation` purpose is set.
+    Revoked,
+
+    /// A status list entry with the `suspension` purpose is set.
+    Suspended,
+
+    /// The credential `validFrom` date is in the future.
+    NotYetValid,
+
+    /// The credential `validUntil` date is in the past.
+    Expired,
+
+    /// The proof `expires` date is in the past.
+    ProofExpired,
+
+    /// The proof signature does not match the credential and optical data.
+    SignatureInvalid,
+
+    /// A credential status could not be retrieved.
+    StatusUnavailable,
+
+    /// The AAMVA protected component index is empty.
+    ///
+    /// See [`VerificationParameters::allow_empty_protected_set`].
+    NothingProtected,
+
+    /// The verification method key curve is below the required minimum.
+    ///
+    /// See [`VerificationParameters::min_curve`].
+    WeakKey,
+}
+
+/// Verifies an optical barcode credential, reporting which check failed.
+///
+/// Checks are performed in the same order as [`verify`]. Errors are only
+/// returned when the proof cannot be processed at all (for instance if the
+/// verification method cannot be resolved).
+pub async fn verify_detailed<T, R, C>(
+    vc: &DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>,
+    extra_information: &T::ExtraInformation,
+    params: VerificationParameters<R, C>,
+) -> Result<VerificationOutcome, ProofValidationError>
+where
+    T: OpticalBarcodeCredentialSubject,
+    R: VerificationMethodResolver<Method = Multikey>,
+    C: TerseStatusListProvider,
+{
+    let optical_data = vc
+        .credential_subjects
+        .first()
+        .unwrap()
+        .create_optical_data(extra_information);
+    verify_detailed_from_optical_data(vc, &optical_data, params).await
+}
+
+/// Verifies an optical barcode credential against the given optical data,
+/// reporting which check failed.
+///
+/// See [`verify_detailed`].
+pub async fn verify_detailed_from_optical_data<T, R, C>(
+    vc: &DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>,
+    optical_data: impl Into<Vec<u8>>,
+    params: VerificationParameters<R, C>,
+) -> Result<VerificationOutcome, ProofValidationError>
+where
+    T: OpticalBarcodeCredentialSubject,
+    R: VerificationMethodResolver<Method = Multikey>,
+    C: TerseStatusListProvider,
+{
+    verify_detailed_from_optical_data_with_statuses(vc, optical_data, params)
+        .await
+        .map(|(outcome, _)| outcome)
+}
+
+/// Verifies an optical barcode credential, reporting which check failed
+/// along with every resolved credential status.
+///
+/// Unlike [`verify_detailed`], all the status entries are resolved even if
+/// one of them is set, so that the returned statuses can be displayed
+/// (e.g. "not revoked, suspended"). They are listed in the order of the
+/// credential `credentialStatus` entries, skipping the ones that could not be
+/// retrieved. No status is returned when status checks are ignored.
+pub async fn verify_detailed_with_statuses<T, R, C>(
+    vc: &DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>,
+    extra_information: &T::ExtraInformation,
+    params: VerificationParameters<R, C>,
+) -> Result<(VerificationOutcome, Vec<(StatusPurpose, u8)>), ProofValidationError>
+where
+    T: OpticalBarcodeCredentialSubject,
+    R: VerificationMethodResolver<Method = Multikey>,
+    C: TerseStatusListProvider,
+{
+    let optical_data = vc
+        .credential_subjects
+        .first()
+        .unwrap()
+        .create_optical_data(extra_information);
+    verify_detailed_from_optical_data_with_statuses(vc, &optical_data, params).await
+}
+
+/// Verifies an optical barcode credential against the given optical data,
+/// reporting which check failed along with every resolved credential status.
+///
+/// See [`verify_detailed_with_statuses`].
+pub async fn verify_detailed_from_optical_data_with_statuses<T, R, C>(
+    vc: &DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>,
+    optical_data: impl Into<Vec<u8>>,
+    params: VerificationParameters<R, C>,
+) -> Result<(VerificationOutcome, Vec<(StatusPurpose, u8)>), ProofValidationError>
+where
+    T: OpticalBarcodeCredentialSubject,
+    R: VerificationMethodResolver<Method = Multikey>,
+    C: TerseStatusListProvider,
+{
+    let mut statuses = Vec::new();
+    if let Err(failure) = check_status_and_validity_period(vc, &params, Some(&mut statuses)).await {
+        return Ok((failure.outcome(), statuses));
+    }
 
-        let status = status.ok_or_else(|| ProofValidationError::other("missing status"))?;
+    let cache = Mutex::new(HashMap::new());
+    let resolver = CachingResolver {
+        resolver: &params.resolver,
+        cache: &cache,
+    };
 
-        match status_purpose {
-            StatusPurpose::Revocation => {
-                if status != 0 {
-                    return Err(ProofValidationError::other("revoked"));
+    if let Err(failure) = check_key_policy(vc, &resolver, params.min_curve).await? {
+        return Ok((failure.outcome(), statuses));
+    }
+
+    let extra_information = ExtraInformation::OpticalData(optical_data.into());
+    match verify_proof(
+        vc,
+        extra_information,
+        resolver,
+        params.date_time,
+        params.require_valid_period,
+    )
+    .await?
+    {
+        Ok(()) => Ok((VerificationOutcome::Valid, statuses)),
+        Err(_) => Ok((VerificationOutcome::SignatureInvalid, statuses)),
+    }
+}
+
+/// Credential status or validity period check failure.
+enum CheckFailure {
+    Revoked,
+    Suspended,
+    NotYetValid,
+    Expired,
+    ProofExpired,
+    StatusUnavailable(ProofValidationError),
+    NothingProtected,
+    WeakKey { curve: Curve, min_curve: Curve },
+}
+
+impl CheckFailure {
+    fn outcome(&self) -> VerificationOutcome {
+        match self {
+            Self::Revoked => VerificationOutcome::Revoked,
+            Self::Suspended => VerificationOutcome::Suspended,
+            Self::NotYetValid => VerificationOutcome::NotYetValid,
+            Self::Expired => VerificationOutcome::Expired,
+            Self::ProofExpired => VerificationOutcome::ProofExpired,
+            Self::StatusUnavailable(_) => VerificationOutcome::StatusUnavailable,
+            Self::NothingProtected => VerificationOutcome::NothingProtected,
+            Self::WeakKey { .. } => VerificationOutcome::WeakKey,
+        }
+    }
+
+    fn into_error(self) -> ProofValidationError {
+        match self {
+            Self::Revoked => ProofValidationError::other("revoked"),
+            Self::Suspended => ProofValidationError::other("suspended"),
+            Self::NotYetValid => ProofValidationError::other("not yet valid"),
+            Self::Expired => ProofValidationError::other("expired"),
+            Self::ProofExpired => ProofValidationError::other("proof expired"),
+            Self::StatusUnavailable(e) => e,
+            Self::NothingProtected => ProofValidationError::other("no protected component"),
+            Self::WeakKey { curve, min_curve } => ProofValidationError::other(format!(
+                "key curve below policy: {curve} (required {min_curve} or above)"
+            )),
+        }
+    }
+}
+
+/// Checks that the credential protects some data, then its statuses, its
+/// validity period and the proofs expiration date, returning the statuses
+/// with the `message` purpose.
+///
+/// The first status failure is reported as soon as it is found, unless
+/// `resolved` is given: every status entry is then resolved, and pushed to
+/// `resolved`, before the first status failure (in entry order) is reported.
+async fn check_status_and_validity_period<T, R, C>(
+    vc: &DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>,
+    params: &VerificationParameters<R, C>,
+    mut resolved: Option<&mut Vec<(StatusPurpose, u8)>>,
+) -> Result<Vec<StatusMessage>, CheckFailure>
+where
+    T: OpticalBarcodeCredentialSubject,
+    C: TerseStatusListProvider,
+{
+    if !params.allow_empty_protected_set
+        && vc
+            .credential_subjects
+            .iter()
+            .any(|s| s.protected_elements().is_some_and(|e| e.is_empty()))
+    {
+        return Err(CheckFailure::NothingProtected);
+    }
+
+    let mut messages = Vec::new();
+    let mut failure = None;
+
+    let statuses = if params.ignore_status {
+        &[][..]
+    } else {
+        vc.credential_status.as_slice()
+    };
+
+    for (entry_index, terse_entry) in statuses.iter().enumerate() {
+        let client = params.status_list_client.as_ref().ok_or_else(|| {
+            CheckFailure::StatusUnavailable(ProofValidationError::other(
+                "no status list parameters",
+            ))
+        })?;
+
+        let result = match client.get_status(terse_entry).await {
+            Ok((status_purpose, Some(status))) => {
+                if let Some(resolved) = resolved.as_deref_mut() {
+                    resolved.push((status_purpose, status));
                 }
-            }
-            StatusPurpose::Suspension => {
-                if status != 0 {
-                    return Err(ProofValidationError::other("suspended"));
+
+                match status_purpose {
+                    StatusPurpose::Revocation if status != 0 => Err(CheckFailure::Revoked),
+                    StatusPurpose::Suspension if status != 0 => Err(CheckFailure::Suspended),
+                    StatusPurpose::Message => {
+                        messages.push(StatusMessage {
+                            entry_index,
+                            status,
+                        });
+                        Ok(())
+                    }
+                    _ => Ok(()),
                 }
             }
-            StatusPurpose::Message => (),
+            Ok((_, None)) => Err(CheckFailure::StatusUnavailable(
+                ProofValidationError::other("missing status"),
+            )),
+            Err(e) => Err(CheckFailure::StatusUnavailable(
+                ProofValidationError::other(e),
+            )),
+        };
+
+        if let Err(e) = result {
+            if resolved.is_none() {
+                return Err(e);
+            }
+
+            failure.get_or_insert(e);
+        }
+    }
+
+    if let Some(failure) = failure {
+        return Err(failure);
+    }
+
+    if params.require_valid_period {
+        // Only the validity period is checked by `validate_credential`.
+        match vc.claims.validate_credential(params) {
+            Ok(()) => (),
+            Err(InvalidClaims::Premature { .. }) => return Err(CheckFailure::NotYetValid),
+            Err(_) => return Err(CheckFailure::Expired),
         }
     }
 
+    let now = params.date_time();
+    for proof in vc.proofs.iter() {
+        if let Some(expires) = proof.expires {
+            let expires: DateTime<Utc> = expires.into();
+            if now > expires {
+                return Err(CheckFailure::ProofExpired);
+            }
+        }
+    }
+
+    Ok(messages)
+}
+
+/// Resolves the proof verification methods, and checks their key curve
+/// against the given minimum.
+///
+/// The resolver should cache the methods, since they are resolved again to
+/// verify the proof.
+async fn check_key_policy<T, R>(
+    vc: &DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>,
+    resolver: &R,
+    min_curve: Option<Curve>,
+) -> Result<Result<(), CheckFailure>, ProofValidationError>
+where
+    T: OpticalBarcodeCredentialSubject,
+    R: VerificationMethodResolver<Method = Multikey>,
+{
+    let Some(min_curve) = min_curve else {
+        return Ok(Ok(()));
+    };
+
+    for proof in vc.proofs.iter() {
+        let method = resolver
+            .resolve_verification_method_with(
+                None,
+                Some(proof.verification_method.borrowed()),
+                ResolutionOptions::default(),
+            )
+            .await
+            .map_err(ProofValidationError::other)?;
+
+        let curve = Curve::of(&method).map_err(ProofValidationError::other)?;
+        if curve < min_curve {
+            return Ok(Err(CheckFailure::WeakKey { curve, min_curve }));
+        }
+    }
+
+    Ok(Ok(()))
+}
+
+/// Verifies the credential proofs.
+///
+/// ssi validates the credential claims before the proofs, and for a VC v2
+/// credential those claims are its validity period only. If `validate_claims`
+/// is `false`, this validation is skipped and the proofs are validated
+/// directly, at the same `date_time`.
+async fn verify_proof<T, R>(
+    vc: &DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>,
+    extra_information: ExtraInformation,
+    resolver: R,
+    date_time: Option<DateTime<Utc>>,
+    validate_claims: bool,
+) -> Result<Verification, ProofValidationError>
+where
+    T: OpticalBarcodeCredentialSubject,
+    R: VerificationMethodResolver<Method = Multikey>,
+{
     let params = XiVerificationParameters::new(
-        optical_data.into(),
+        extra_information,
         ssi::claims::VerificationParameters {
-            resolver: params.resolver,
+            resolver,
             json_ld_loader: &*CONTEXT_LOADER,
             eip712_types_loader: (),
-            date_time: params.date_time,
+            date_time,
         },
     );
 
-    vc.verify(params).await
+    if validate_claims {
+        vc.verify(params).await
+    } else {
+        let validity = vc.proofs.validate_proof(&params, &vc.claims).await?;
+        Ok(validity.map_err(Invalid::Proof))
+    }
+}
+
+/// Verifies many optical barcode credentials against their optical data,
+/// sharing the same resolver and status list client.
+///
+/// Each distinct verification method is resolved only once. Results are
+/// returned in the same order as `items`, and a failure on one item does not
+/// affect the others.
+pub async fn verify_batch<T, R, C>(
+    items: &[(VerifiableOpticalBarcodeCredential<T>, Vec<u8>)],
+    params: VerificationParameters<R, C>,
+) -> Vec<Result<Verification, ProofValidationError>>
+where
+    T: OpticalBarcodeCredentialSubject,
+    R: VerificationMethodResolver<Method = Multikey>,
+    C: TerseStatusListProvider,
+{
+    let cache = Mutex::new(HashMap::new());
+    let mut results = Vec::with_capacity(items.len());
+
+    for (vc, optical_data) in items {
+        let item_params = VerificationParameters {
+            resolver: CachingResolver {
+                resolver: &params.resolver,
+                cache: &cache,
+            },
+            status_list_client: params.status_list_client.as_ref(),
+            date_time: params.date_time,
+            require_valid_period: params.require_valid_period,
+            ignore_status: params.ignore_status,
+            allow_empty_protected_set: params.allow_empty_protected_set,
+            min_curve: params.min_curve,
+        };
+
+        results.push(verify_from_optical_data(vc, optical_data.as_slice(), item_params).await)
+    }
+
+    results
+}
+
+/// Verifies an optical barcode credential against its optical data, with an
+/// already resolved issuer key.
+///
+/// This is meant for verifiers obtaining the issuer keys out-of-band, e.g.
+/// from a pre-distributed list of jurisdiction keys, without resolving any
+/// verification method. The proof verification method must be `key` itself,
+/// referenced by its `id`.
+///
+/// The `params` resolver is unused: build them with
+/// `VerificationParameters::new(())`.
+pub async fn verify_with_key<T, C>(
+    vc: &VerifiableOpticalBarcodeCredential<T>,
+    optical_data: impl Into<Vec<u8>>,
+    key: &Multikey,
+    params: VerificationParameters<(), C>,
+) -> Result<Verification, ProofValidationError>
+where
+    T: OpticalBarcodeCredentialSubject,
+    C: TerseStatusListProvider,
+{
+    let params = VerificationParameters {
+        resolver: SingleKeyResolver(key),
+        status_list_client: params.status_list_client,
+        date_time: params.date_time,
+        require_valid_period: params.require_valid_period,
+        ignore_status: params.ignore_status,
+        allow_empty_protected_set: params.allow_empty_protected_set,
+        min_curve: params.min_curve,
+    };
+
+    verify_from_optical_data(vc, optical_data, params).await
+}
+
+/// Verification method resolver only resolving the given key.
+///
+/// The key is returned when referenced by its `id`. Any other verification
+/// method fails with [`VerificationMethodResolutionError::InvalidKeyId`]. An
+/// embedded verification method with the same `id` resolves to the given key,
+/// not to the embedded one.
+#[derive(Debug, Clone, Copy)]
+pub struct SingleKeyResolver<'a>(pub &'a Multikey);
+
+impl VerificationMethodResolver for SingleKeyResolver<'_> {
+    type Method = Multikey;
+
+    async fn resolve_verification_method_with(
+        &self,
+        _issuer: Option<&Iri>,
+        method: Option<ReferenceOrOwnedRef<'_, Multikey>>,
+        _options: ResolutionOptions,
+    ) -> Result<Cow<Multikey>, VerificationMethodResolutionError> {
+        let id = match method {
+            Some(ReferenceOrOwnedRef::Reference(id)) => id.as_str(),
+            Some(ReferenceOrOwnedRef::Owned(method)) => method.id.as_str(),
+            None => return Err(VerificationMethodResolutionError::MissingVerificationMethod),
+        };
+
+        if id == self.0.id.as_str() {
+            Ok(Cow::Borrowed(self.0))
+        } else {
+            Err(VerificationMethodResolutionError::InvalidKeyId(
+                id.to_owned(),
+            ))
+        }
+    }
+}
+
+/// Verification method resolver caching methods resolved by reference.
+struct CachingResolver<'a, R> {
+    resolver: &'a R,
+    cache: &'a Mutex<HashMap<IriBuf, Multikey>>,
+}
+
+impl<R> VerificationMethodResolver for CachingResolver<'_, R>
+where
+    R: VerificationMethodResolver<Method = Multikey>,
+{
+    type Method = Multikey;
+
+    async fn resolve_verification_method_with(
+        &self,
+        issuer: Option<&Iri>,
+        method: Option<ReferenceOrOwnedRef<'_, Multikey>>,
+        options: ResolutionOptions,
+    ) -> Result<Cow<Multikey>, VerificationMethodResolutionError> {
+        let Some(ReferenceOrOwnedRef::Reference(id)) = method else {
+            return self
+                .resolver
+                .resolve_verification_method_with(issuer, method, options)
+                .await;
+        };
+
+        let cached = self.cache.lock().unwrap().get(id).cloned();
+        if let Some(method) = cached {
+            return Ok(Cow::Owned(method));
+        }
+
+        let method = self
+            .resolver
+            .resolve_verification_method_with(issuer, method, options)
+            .await?
+            .into_owned();
+
+        self.cache
+            .lock()
+            .unwrap()
+            .insert(id.to_owned(), method.clone());
+
+        Ok(Cow::Owned(method))
+    }
+}
+
+/// Verification method resolver memoizing the resolved keys by verification
+/// method IRI, for a limited time.
+///
+/// This is meant for scenarios repeatedly verifying credentials from the same
+/// few issuers (e.g. a kiosk), where DID resolution dominates the
+/// verification latency. Unlike [`verify_batch`], which shares resolutions
+/// within a single batch, the cache lives as long as the resolver: pass it by
+/// reference to [`VerificationParameters`] to reuse it.
+///
+/// Only methods referenced by IRI are cached. Embedded methods are passed
+/// through to the underlying resolver.
+pub struct CachingVmResolver<R> {
+    resolver: R,
+    ttl: Duration,
+    cache: Mutex<HashMap<IriBuf, (Option<DateTime<Utc>>, Multikey)>>,
+}
+
+impl<R> CachingVmResolver<R> {
+    /// Creates a new caching resolver, keeping resolved methods for `ttl`.
+    pub fn new(resolver: R, ttl: Duration) -> Self {
+        Self {
+            resolver,
+            ttl,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Forgets every cached verification method.
+    pub fn clear(&self) {
+        self.cache.lock().unwrap().clear()
+    }
+}
+
+impl<R> VerificationMethodResolver for CachingVmResolver<R>
+where
+    R: VerificationMethodResolver<Method = Multikey>,
+{
+    type Method = Multikey;
+
+    async fn resolve_verification_method_with(
+        &self,
+        issuer: Option<&Iri>,
+        method: Option<ReferenceOrOwnedRef<'_, Multikey>>,
+        options: ResolutionOptions,
+    ) -> Result<Cow<Multikey>, VerificationMethodResolutionError> {
+        let Some(ReferenceOrOwnedRef::Reference(id)) = method else {
+            return self
+                .resolver
+                .resolve_verification_method_with(issuer, method, options)
+                .await;
+        };
+
+        let now = Utc::now();
+        let cached = {
+            let mut cache = self.cache.lock().unwrap();
+            cache.retain(|_, (expires, _)| expires.map_or(true, |expires| now < expires));
+            cache.get(id).map(|(_, method)| method.clone())
+        };
+
+        if let Some(method) = cached {
+            return Ok(Cow::Owned(method));
+        }
+
+        let method = self
+            .resolver
+            .resolve_verification_method_with(
+                issuer,
+                Some(ReferenceOrOwnedRef::Reference(id)),
+                options,
+            )
+            .await?
+            .into_owned();
+
+        // A TTL too large to be represented never expires.
+        let expires = ssi::claims::chrono::Duration::from_std(self.ttl)
+            .ok()
+            .and_then(|ttl| now.checked_add_signed(ttl));
+
+        self.cache
+            .lock()
+            .unwrap()
+            .insert(id.to_owned(), (expires, method.clone()));
+
+        Ok(Cow::Owned(method))
+    }
 }
 
 struct XiVerificationParameters<P> {
@@ -119,10 +930,16 @@ struct XiVerificationParameters<P> {
     params: P,
 }
 
+impl<R, C> DateTimeProvider for VerificationParameters<R, C> {
+    fn date_time(&self) -> DateTime<Utc> {
+        self.date_time.unwrap_or_else(Utc::now)
+    }
+}
+
 impl<P> XiVerificationParameters<P> {
-    fn new(extra_information: Vec<u8>, params: P) -> Self {
+    fn new(extra_information: ExtraInformation, params: P) -> Self {
         Self {
-            extra_information: ExtraInformation(extra_information),
+            extra_information,
             params,
         }
     }
@@ -0,0 +1,71 @@
+//! Barcode payload sources.
+//!
+//! [`BarcodeSource`] is the common front door to the credential decoding
+//! functions: whatever the barcode format, it provides the CBOR-LD bytes to
+//! pass to `optical_barcode_credential::decode_from_bytes`. This crate does
+//! not decode images. Barcode reader crates are expected to implement this
+//! trait, or to feed their output to one of the adapters provided here.
+use alloc::{format, vec::Vec};
+
+use crate::{io, mrz::MachineReadableZone};
+
+/// Source of an optical barcode credential payload.
+pub trait BarcodeSource {
+    /// Reads the CBOR-LD bytes of the credential.
+    fn read_payload(&self) -> io::Result<Vec<u8>>;
+}
+
+impl<T: ?Sized + BarcodeSource> BarcodeSource for &T {
+    fn read_payload(&self) -> io::Result<Vec<u8>> {
+        T::read_payload(*self)
+    }
+}
+
+/// Raw CBOR-LD bytes.
+impl BarcodeSource for [u8] {
+    fn read_payload(&self) -> io::Result<Vec<u8>> {
+        Ok(self.to_vec())
+    }
+}
+
+/// Raw CBOR-LD bytes.
+impl BarcodeSource for Vec<u8> {
+    fn read_payload(&self) -> io::Result<Vec<u8>> {
+        Ok(self.clone())
+    }
+}
+
+/// `VC1-` prefixed QR code (or Aztec code) payload.
+///
+/// See [`MachineReadableZone::decode_qr_code_payload`].
+#[derive(Debug, Clone, Copy)]
+pub struct QrCodePayload<'a>(pub &'a str);
+
+impl BarcodeSource for QrCodePayload<'_> {
+    fn read_payload(&self) -> io::Result<Vec<u8>> {
+        MachineReadableZone::decode_qr_code_payload(self.0)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{e}")))
+    }
+}
+
+/// AAMVA PDF417 file bytes, with the credential in its `ZZ` subfile.
+///
+/// See [`ZZSubfile`](crate::aamva::ZZSubfile).
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct Pdf417Payload<'a>(pub &'a [u8]);
+
+#[cfg(feature = "std")]
+impl BarcodeSource for Pdf417Payload<'_> {
+    fn read_payload(&self) -> io::Result<Vec<u8>> {
+        use crate::aamva::{decode_zz_payload, dlid::pdf_417::File, ZZSubfile};
+
+        let mut cursor = io::Cursor::new(self.0);
+        let zz: ZZSubfile = File::new(&mut cursor)?
+            .read_subfile(b"ZZ")?
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing `ZZ` subfile"))?;
+
+        decode_zz_payload(&zz.zza)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{e}")))
+    }
+}
@@ -1,3 +1,5 @@
+use std::fmt::Write;
+
 use iref::{Uri, UriBuf};
 use lazy_static::lazy_static;
 use serde::{Deserialize, Serialize};
@@ -12,6 +14,7 @@ use ssi::{
 };
 
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum IncompressibleStatusListEntry {
     #[error("missing list index")]
     MissingListIndex,
@@ -29,7 +32,46 @@ pub enum IncompressibleStatusListEntry {
     UnexpectedStatusPurpose,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl IncompressibleStatusListEntry {
+    /// Checks if this is a [`Self::MissingListIndex`] error.
+    pub fn is_missing_list_index(&self) -> bool {
+        matches!(self, Self::MissingListIndex)
+    }
+
+    /// Checks if this is a [`Self::InvalidListIndex`] error.
+    pub fn is_invalid_list_index(&self) -> bool {
+        matches!(self, Self::InvalidListIndex)
+    }
+
+    /// Checks if this is a [`Self::MissingStatusPurpose`] error.
+    pub fn is_missing_status_purpose(&self) -> bool {
+        matches!(self, Self::MissingStatusPurpose)
+    }
+
+    /// Checks if this is a [`Self::InvalidStatusPurpose`] error.
+    pub fn is_invalid_status_purpose(&self) -> bool {
+        matches!(self, Self::InvalidStatusPurpose)
+    }
+
+    /// Checks if this is a [`Self::UnexpectedStatusPurpose`] error.
+    pub fn is_unexpected_status_purpose(&self) -> bool {
+        matches!(self, Self::UnexpectedStatusPurpose)
+    }
+}
+
+/// Invalid terse bit-string status list entry components.
+///
+/// See [`TerseBitstringStatusListEntry::try_new`].
+#[derive(Debug, thiserror::Error)]
+pub enum InvalidTerseStatusListEntry {
+    #[error("empty status lists")]
+    EmptyStatusList,
+
+    #[error("base URL ends with status list segments")]
+    AmbiguousBaseUrl,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub struct TerseBitstringStatusListEntry {
     #[serde(rename = "terseStatusListBaseUrl")]
@@ -44,29 +86,68 @@ impl TerseBitstringStatusListEntry {
         Self { base_url, index }
     }
 
+    /// Creates a new terse bit-string status list entry, checking that it
+    /// can be expanded with the given status list info.
+    ///
+    /// Fails if `info.list_len` is zero, or if `base_url` already ends with
+    /// `{status_purpose}` or `{status_purpose}/{list_index}` segments, which
+    /// would be indistinguishable from the ones appended by
+    /// [`Self::status_list_url`].
+    pub fn try_new(
+        base_url: UriBuf,
+        index: u32,
+        info: StatusListInfo,
+    ) -> Result<Self, InvalidTerseStatusListEntry> {
+        if info.list_len == 0 {
+            return Err(InvalidTerseStatusListEntry::EmptyStatusList);
+        }
+
+        let mut segments = base_url
+            .path()
+            .as_str()
+            .trim_end_matches('/')
+            .rsplit('/')
+            .take(2);
+        let is_purpose = |s: &str| s.parse::<StatusPurpose>().is_ok();
+        let ambiguous = match (segments.next(), segments.next()) {
+            (Some(last), _) if is_purpose(last) => true,
+            (Some(last), Some(previous)) => last.parse::<u32>().is_ok() && is_purpose(previous),
+            _ => false,
+        };
+
+        if ambiguous {
+            return Err(InvalidTerseStatusListEntry::AmbiguousBaseUrl);
+        }
+
+        Ok(Self { base_url, index })
+    }
+
     /// Creates a new terse bit-string status list entry from a
     /// [`BitstringStatusListEntry`].
+    ///
+    /// The status list credential URL is expected to be of the form
+    /// `{base_url}/{status_purpose}/{list_index}`, where any query or
+    /// fragment is kept as part of the base URL.
     pub fn from_bitstring_status_list_entry(
         status: BitstringStatusListEntry,
         list_len: usize,
     ) -> Result<Self, IncompressibleStatusListEntry> {
-        let mut terse_status_list_base_url = status.status_list_credential;
+        let status_list_credential = status.status_list_credential;
+        let path = status_list_credential.path().as_str().trim_end_matches('/');
 
-        let list_index: u32 = terse_status_list_base_url
-            .path()
-            .last()
-            .ok_or(IncompressibleStatusListEntry::MissingListIndex)?
-            .as_str()
+        let (path, list_index) = path
+            .rsplit_once('/')
+            .ok_or(IncompressibleStatusListEntry::MissingListIndex)?;
+
+        let list_index: u32 = list_index
             .parse()
             .map_err(|_| IncompressibleStatusListEntry::InvalidListIndex)?;
 
-        terse_status_list_base_url.path_mut().pop();
+        let (base_path, status_purpose) = path
+            .rsplit_once('/')
+            .ok_or(IncompressibleStatusListEntry::MissingStatusPurpose)?;
 
-        let status_purpose: StatusPurpose = terse_status_list_base_url
-            .path()
-            .last()
-            .ok_or(IncompressibleStatusListEntry::MissingStatusPurpose)?
-            .as_str()
+        let status_purpose: StatusPurpose = status_purpose
             .parse()
             .map_err(|_| IncompressibleStatusListEntry::InvalidStatusPurpose)?;
 
@@ -74,13 +155,11 @@ impl TerseBitstringStatusListEntry {
             return Err(IncompressibleStatusListEntry::UnexpectedStatusPurpose);
         }
 
-        terse_status_list_base_url.path_mut().pop();
-
         let terse_status_list_index =
             list_index * list_len as u32 + status.status_list_index as u32;
 
         Ok(Self {
-            base_url: terse_status_list_base_url,
+            base_url: with_path(&status_list_credential, base_path),
             index: terse_status_list_index,
         })
     }
@@ -92,20 +171,61 @@ impl TerseBitstringStatusListEntry {
     ///
     /// See: <https://w3c-ccg.github.io/vc-barcodes/#convert-status-list-entries>
     pub fn to_bitstring_status_list_entry(&self, info: StatusListInfo) -> BitstringStatusListEntry {
-        let list_index = self.index as usize / info.list_len;
         let status_list_index = self.index as usize % info.list_len;
-        let status_list_credential = UriBuf::new(
-            format!("{}/{}/{list_index}", self.base_url, info.status_purpose).into_bytes(),
-        )
-        .unwrap();
 
         BitstringStatusListEntry::new(
             None,
             info.status_purpose,
-            status_list_credential,
+            self.status_list_url(info),
             status_list_index,
         )
     }
+
+    /// Returns the URL of the status list credential this entry refers to,
+    /// of the form `{base_url}/{status_purpose}/{list_index}`.
+    pub fn status_list_url(&self, info: StatusListInfo) -> UriBuf {
+        let list_index = self.index as usize / info.list_len;
+        let path = format!(
+            "{}/{}/{list_index}",
+            self.base_url.path().as_str().trim_end_matches('/'),
+            info.status_purpose
+        );
+
+        with_path(&self.base_url, &path)
+    }
+
+    /// Returns the status list credential URL and the index of the status in
+    /// that list.
+    ///
+    /// Entries sharing the same URL can be resolved with a single status
+    /// list fetch.
+    pub fn cache_key(&self, info: StatusListInfo) -> (UriBuf, usize) {
+        (
+            self.status_list_url(info),
+            self.index as usize % info.list_len,
+        )
+    }
+}
+
+/// Replaces the path of the given URI, preserving every other component.
+fn with_path(uri: &Uri, path: &str) -> UriBuf {
+    let mut result = format!("{}:", uri.scheme());
+
+    if let Some(authority) = uri.authority() {
+        write!(result, "//{authority}").unwrap();
+    }
+
+    result.push_str(path);
+
+    if let Some(query) = uri.query() {
+        write!(result, "?{query}").unwrap();
+    }
+
+    if let Some(fragment) = uri.fragment() {
+        write!(result, "#{fragment}").unwrap();
+    }
+
+    UriBuf::new(result.into_bytes()).unwrap()
 }
 
 impl MaybeIdentified for TerseBitstringStatusListEntry {
@@ -160,6 +280,18 @@ pub trait TerseStatusListProvider {
     }
 }
 
+impl<C: TerseStatusListProvider> TerseStatusListProvider for &C {
+    async fn get(
+        &self,
+        terse_entry: &TerseBitstringStatusListEntry,
+    ) -> Result<
+        (MaybeCached<StatusList>, BitstringStatusListEntry),
+        ssi::status::client::ProviderError,
+    > {
+        C::get(*self, terse_entry).await
+    }
+}
+
 pub struct NoTerseStatusListProvider;
 
 impl TerseStatusListProvider for NoTerseStatusListProvider {
@@ -176,6 +308,14 @@ impl TerseStatusListProvider for NoTerseStatusListProvider {
     }
 }
 
+/// Terse status list provider using the same [`StatusListInfo`] for every
+/// entry.
+///
+/// The terse index can only be split into a list index and a status list index
+/// once the list length is known, so it has to be configured up front. To
+/// prevent a wrong `list_len` from silently reading the wrong bit, the length
+/// of every fetched status list is checked against it, and a mismatch is
+/// reported as an error.
 pub struct ConstTerseStatusListProvider<C> {
     pub client: C,
     pub info: StatusListInfo,
@@ -200,6 +340,159 @@ where
     > {
         let entry = terse_entry.to_bitstring_status_list_entry(self.info);
         let list = self.client.get_typed(&entry.status_list_credential).await?;
+
+        let (MaybeCached::Cached(status_list) | MaybeCached::NotCached(status_list)) = &list;
+        if status_list.len() != self.info.list_len {
+            return Err(ssi::status::client::ProviderError::Internal(format!(
+                "status list length mismatch: expected {}, found {}",
+                self.info.list_len,
+                status_list.len()
+            )));
+        }
+
         Ok((list, entry))
     }
 }
+
+/// Terse status list provider trying a `primary` provider first, then a
+/// `fallback` provider if the first one fails.
+///
+/// This can be chained to try, for instance, a local cache, then an HTTP
+/// endpoint. When both providers fail, the returned error reports both
+/// failures.
+pub struct FallbackStatusListProvider<A, B> {
+    pub primary: A,
+    pub fallback: B,
+}
+
+impl<A, B> FallbackStatusListProvider<A, B> {
+    pub fn new(primary: A, fallback: B) -> Self {
+        Self { primary, fallback }
+    }
+}
+
+impl<A, B> TerseStatusListProvider for FallbackStatusListProvider<A, B>
+where
+    A: TerseStatusListProvider,
+    B: TerseStatusListProvider,
+{
+    async fn get(
+        &self,
+        terse_entry: &TerseBitstringStatusListEntry,
+    ) -> Result<
+        (MaybeCached<StatusList>, BitstringStatusListEntry),
+        ssi::status::client::ProviderError,
+    > {
+        match self.primary.get(terse_entry).await {
+            Ok(result) => Ok(result),
+            Err(primary) => self.fallback.get(terse_entry).await.map_err(|fallback| {
+                ssi::status::client::ProviderError::Internal(format!(
+                    "primary provider failed: {primary}; fallback provider failed: {fallback}"
+                ))
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ssi::status::bitstring_status_list_20240406::StatusPurpose;
+    use static_iref::uri;
+
+    use super::{InvalidTerseStatusListEntry, StatusListInfo, TerseBitstringStatusListEntry};
+
+    #[test]
+    fn round_trip_with_port_and_query() {
+        let terse = TerseBitstringStatusListEntry::new(
+            uri!("https://issuer.example:8443/status?v=2").to_owned(),
+            3042,
+        );
+
+        let info = StatusListInfo::new(1000, StatusPurpose::Revocation);
+        let entry = terse.to_bitstring_status_list_entry(info);
+        assert_eq!(
+            entry.status_list_credential.as_str(),
+            "https://issuer.example:8443/status/revocation/3?v=2"
+        );
+        assert_eq!(terse.status_list_url(info), entry.status_list_credential);
+        assert_eq!(entry.status_list_index, 42);
+
+        let result =
+            TerseBitstringStatusListEntry::from_bitstring_status_list_entry(entry, 1000).unwrap();
+        assert_eq!(result.base_url, terse.base_url);
+        assert_eq!(result.index, terse.index)
+    }
+
+    #[test]
+    fn trailing_slash() {
+        let terse = TerseBitstringStatusListEntry::new(
+            uri!("https://issuer.example/status/").to_owned(),
+            5,
+        );
+
+        let info = StatusListInfo::new(1000, StatusPurpose::Suspension);
+        let entry = terse.to_bitstring_status_list_entry(info);
+        assert_eq!(
+            entry.status_list_credential.as_str(),
+            "https://issuer.example/status/suspension/0"
+        );
+
+        let result =
+            TerseBitstringStatusListEntry::from_bitstring_status_list_entry(entry, 1000).unwrap();
+        assert_eq!(result.base_url.as_str(), "https://issuer.example/status")
+    }
+
+    #[test]
+    fn cache_key() {
+        let info = StatusListInfo::new(1000, StatusPurpose::Revocation);
+        let base_url = uri!("https://issuer.example/status");
+
+        let a = TerseBitstringStatusListEntry::new(base_url.to_owned(), 3042);
+        let b = TerseBitstringStatusListEntry::new(base_url.to_owned(), 3999);
+        assert_ne!(a, b);
+        assert_eq!(a, a.clone());
+
+        let (url_a, index_a) = a.cache_key(info);
+        let (url_b, index_b) = b.cache_key(info);
+        assert_eq!(url_a, url_b);
+        assert_eq!((index_a, index_b), (42, 999));
+
+        let set: std::collections::HashSet<_> = [a.clone(), a, b].into_iter().collect();
+        assert_eq!(set.len(), 2)
+    }
+
+    #[test]
+    fn try_new() {
+        let info = StatusListInfo::new(1000, StatusPurpose::Revocation);
+
+        let terse = TerseBitstringStatusListEntry::try_new(
+            uri!("https://issuer.example/status").to_owned(),
+            3042,
+            info,
+        )
+        .unwrap();
+        assert_eq!(
+            terse.status_list_url(info).as_str(),
+            "https://issuer.example/status/revocation/3"
+        );
+
+        assert!(matches!(
+            TerseBitstringStatusListEntry::try_new(
+                uri!("https://issuer.example/status").to_owned(),
+                3042,
+                StatusListInfo::new(0, StatusPurpose::Revocation),
+            ),
+            Err(InvalidTerseStatusListEntry::EmptyStatusList)
+        ));
+
+        for base_url in [
+            uri!("https://issuer.example/status/revocation"),
+            uri!("https://issuer.example/status/suspension/3/"),
+        ] {
+            assert!(matches!(
+                TerseBitstringStatusListEntry::try_new(base_url.to_owned(), 3042, info),
+                Err(InvalidTerseStatusListEntry::AmbiguousBaseUrl)
+            ));
+        }
+    }
+}
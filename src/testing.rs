@@ -0,0 +1,65 @@
+//! Deterministic signing helpers, for reproducible tests.
+//!
+//! ECDSA signatures are deterministic ([RFC 6979]), so signing the same
+//! credential with [`jwk`] and [`created`] always produces the same proof,
+//! allowing byte-exact golden-vector tests of the whole
+//! sign/compress/encode/decode/verify pipeline.
+//!
+//! **Never use this key outside of tests**, its private part is public.
+//!
+//! [RFC 6979]: <https://www.rfc-editor.org/rfc/rfc6979>
+use ssi::{
+    claims::data_integrity::ProofOptions,
+    dids::{AnyDidMethod, DIDKey, DIDResolver},
+    verification_methods::SingleSecretSigner,
+    JWK,
+};
+
+use crate::{
+    optical_barcode_credential::{
+        self, OpticalBarcodeCredentialSubject, SignError, SignatureParameters,
+        VerifiableOpticalBarcodeCredential,
+    },
+    DateTime, OpticalBarcodeCredential, Utc,
+};
+
+/// Fixed P-256 test key.
+pub fn jwk() -> JWK {
+    r#"{
+        "kty": "EC",
+        "crv": "P-256",
+        "x": "Rex3s7Qb87WTGIcYCJsyHR2hpQPSb7GEcyHfMaH77ms",
+        "y": "bKkCDu36um-ekUH0g166a3TQu0rpYkPZoFappeS7FD4",
+        "d": "iHcuffvSJVgpcDROr3RlaHEob-QqXgFkgZ5I22CTfaI"
+    }"#
+    .parse()
+    .unwrap()
+}
+
+/// Fixed proof creation date: `2024-01-01T00:00:00Z`.
+pub fn created() -> DateTime<Utc> {
+    "2024-01-01T00:00:00Z".parse().unwrap()
+}
+
+/// Signs the given credential with the test key ([`jwk`]), using its
+/// `did:key` verification method and the fixed [`created`] date.
+pub async fn sign<T>(
+    unsigned: OpticalBarcodeCredential<T>,
+    extra_information: &T::ExtraInformation,
+) -> Result<VerifiableOpticalBarcodeCredential<T>, SignError>
+where
+    T: OpticalBarcodeCredentialSubject,
+{
+    let jwk = jwk();
+    let vm = DIDKey::generate_url(&jwk).unwrap();
+    let options = ProofOptions::from_method(vm.into_iri().into());
+
+    let params = SignatureParameters::new(
+        AnyDidMethod::default().into_vm_resolver(),
+        SingleSecretSigner::new(jwk),
+        None,
+    )
+    .with_created(created());
+
+    optical_barcode_credential::sign(unsigned, extra_information, options, params).await
+}
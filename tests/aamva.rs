@@ -1,4 +1,5 @@
-use json_syntax::Print;
+use iref::UriBuf;
+use json_syntax::{Parse, Print};
 use lazy_static::lazy_static;
 use ssi::{
     dids::{AnyDidMethod, DIDResolver},
@@ -6,15 +7,26 @@ use ssi::{
     verification_methods::SingleSecretSigner,
     JWK,
 };
-use std::io::Cursor;
+use std::io::{BufReader, Cursor};
 use w3c_vc_barcodes::{
     aamva::{
-        dlid::{pdf_417, DlSubfile},
-        AamvaDriversLicenseScannableInformation, ZZSubfile,
+        dlid::{
+            pdf_417::{self, DecodeSubfile, RecordEntry},
+            DlElement, DlMandatoryElement, DlMandatoryElements, DlOptionalElement, DlSubfile,
+            FieldDiff,
+        },
+        AamvaDriversLicenseScannableInformation, CredentialsSubfile, ZZDecodeError, ZZSubfile,
     },
-    optical_barcode_credential::{self, SignatureParameters, VerificationParameters},
-    terse_bitstring_status_list_entry::{ConstTerseStatusListProvider, StatusListInfo},
-    verify,
+    optical_barcode_credential::{
+        self, DecodeError, DecodedVcb, SemanticEq, SignatureParameters, StatusMessage, Summarize,
+        VerificationOutcome, VerificationParameters,
+    },
+    source::{BarcodeSource, Pdf417Payload},
+    terse_bitstring_status_list_entry::{
+        ConstTerseStatusListProvider, FallbackStatusListProvider, NoTerseStatusListProvider,
+        StatusListInfo, TerseBitstringStatusListEntry, TerseStatusListProvider,
+    },
+    verify, MachineReadableZone,
 };
 
 mod common;
@@ -29,6 +41,26 @@ lazy_static! {
     };
 }
 
+#[test]
+fn aamva_dl_subfile_borrowed() {
+    let borrowed = DlSubfile::borrowed(DL_SUBFILE_BYTES.as_bytes()).unwrap();
+    assert_eq!(borrowed.len(), DL_SUBFILE.len());
+    assert!(!borrowed.is_empty());
+
+    for (element, value) in DL_SUBFILE.iter() {
+        assert_eq!(borrowed.get(element), Some(value))
+    }
+
+    assert_eq!(
+        borrowed.mandatory(DlMandatoryElement::CustomerFamilyName),
+        b"SMITH"
+    );
+
+    let missing = DL_SUBFILE_BYTES.replace("DCSSMITH\n", "");
+    assert!(DlSubfile::borrowed(missing.as_bytes()).is_err());
+    assert!(DlSubfile::borrowed(b"IDDACJOHN\r").is_err())
+}
+
 #[async_std::test]
 async fn aamva_sign() {
     let input =
@@ -49,6 +81,28 @@ async fn aamva_sign() {
         .unwrap();
 }
 
+#[async_std::test]
+async fn aamva_sign_prepared() {
+    let input =
+        load_unsigned::<AamvaDriversLicenseScannableInformation>("tests/aamva/unsecured.jsonld");
+    let status = input.credential_status.clone();
+    assert_eq!(status.len(), 1);
+
+    let options = load_proof_configuration("tests/aamva/configuration.jsonld").into_options();
+
+    let params = SignatureParameters::new(
+        AnyDidMethod::default().into_vm_resolver(),
+        SingleSecretSigner::new(JWK::generate_p256()),
+        None,
+    );
+
+    let vc =
+        optical_barcode_credential::sign_prepared(input, &DL_SUBFILE.mandatory, options, params)
+            .await
+            .unwrap();
+    assert_eq!(vc.credential_status, status)
+}
+
 #[async_std::test]
 async fn aamva_verify() {
     let vc = load_signed::<AamvaDriversLicenseScannableInformation>("tests/aamva/secured.jsonld");
@@ -67,6 +121,257 @@ async fn aamva_verify() {
     assert_eq!(result, Ok(()))
 }
 
+#[async_std::test]
+async fn aamva_verify_fallback_status() {
+    let vc = load_signed::<AamvaDriversLicenseScannableInformation>("tests/aamva/secured.jsonld");
+
+    let status_list_client = FallbackStatusListProvider::new(
+        NoTerseStatusListProvider,
+        ConstTerseStatusListProvider::new(
+            StatusLists,
+            StatusListInfo::new(1000, StatusPurpose::Revocation),
+        ),
+    );
+
+    let params = VerificationParameters::new_with(
+        AnyDidMethod::default().into_vm_resolver(),
+        status_list_client,
+    );
+
+    let result = verify(&vc, &DL_SUBFILE.mandatory, params).await.unwrap();
+    assert_eq!(result, Ok(()));
+
+    let status_list_client =
+        FallbackStatusListProvider::new(NoTerseStatusListProvider, NoTerseStatusListProvider);
+    let error = status_list_client
+        .get(&vc.credential_status[0])
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("primary provider failed"));
+    assert!(error.contains("fallback provider failed"))
+}
+
+#[async_std::test]
+async fn aamva_verify_ignore_status() {
+    let vc = load_signed::<AamvaDriversLicenseScannableInformation>("tests/aamva/secured.jsonld");
+
+    let params = VerificationParameters::new(AnyDidMethod::default().into_vm_resolver());
+    assert!(verify(&vc, &DL_SUBFILE.mandatory, params).await.is_err());
+
+    let params =
+        VerificationParameters::new(AnyDidMethod::default().into_vm_resolver()).ignore_status(true);
+    let result = verify(&vc, &DL_SUBFILE.mandatory, params).await.unwrap();
+    assert_eq!(result, Ok(()))
+}
+
+#[async_std::test]
+async fn aamva_verify_empty_protected_set() {
+    let content = std::fs::read_to_string("tests/aamva/secured.jsonld")
+        .unwrap()
+        .replace("\"uggAg\"", "\"uAAAA\"");
+    let json = json_syntax::Value::parse_str(&content).unwrap().0;
+    let vc: optical_barcode_credential::VerifiableOpticalBarcodeCredential<
+        AamvaDriversLicenseScannableInformation,
+    > = json_syntax::from_value(json).unwrap();
+
+    let params =
+        VerificationParameters::new(AnyDidMethod::default().into_vm_resolver()).ignore_status(true);
+    let outcome = optical_barcode_credential::verify_detailed(&vc, &DL_SUBFILE.mandatory, params)
+        .await
+        .unwrap();
+    assert_eq!(outcome, VerificationOutcome::NothingProtected);
+
+    let params = VerificationParameters::new(AnyDidMethod::default().into_vm_resolver())
+        .ignore_status(true)
+        .allow_empty_protected_set(true);
+    let outcome = optical_barcode_credential::verify_detailed(&vc, &DL_SUBFILE.mandatory, params)
+        .await
+        .unwrap();
+    assert_eq!(outcome, VerificationOutcome::SignatureInvalid)
+}
+
+#[async_std::test]
+async fn aamva_validate_against_index() {
+    let vc = load_signed::<AamvaDriversLicenseScannableInformation>("tests/aamva/secured.jsonld");
+    let params = || {
+        VerificationParameters::new(AnyDidMethod::default().into_vm_resolver()).ignore_status(true)
+    };
+
+    let result = DL_SUBFILE.validate_against_index(&vc, params()).await;
+    assert_eq!(result.unwrap(), Ok(()));
+
+    let mut tampered = DL_SUBFILE.clone();
+    tampered.mandatory.customer_family_name = b"DOE".to_vec();
+    let result = tampered.validate_against_index(&vc, params()).await;
+    assert!(result.unwrap().is_err());
+
+    // Unprotected fields can be altered.
+    tampered = DL_SUBFILE.clone();
+    tampered.mandatory.date_of_birth = b"01011970".to_vec();
+    let result = tampered.validate_against_index(&vc, params()).await;
+    assert_eq!(result.unwrap(), Ok(()));
+
+    let content = std::fs::read_to_string("tests/aamva/secured.jsonld")
+        .unwrap()
+        .replace("\"uggAg\"", "\"u____\"");
+    let json = json_syntax::Value::parse_str(&content).unwrap().0;
+    let unknown: optical_barcode_credential::VerifiableOpticalBarcodeCredential<
+        AamvaDriversLicenseScannableInformation,
+    > = json_syntax::from_value(json).unwrap();
+    assert!(DL_SUBFILE
+        .validate_against_index(&unknown, params())
+        .await
+        .is_err())
+}
+
+#[async_std::test]
+async fn aamva_verify_detailed_with_statuses() {
+    let vc = load_signed::<AamvaDriversLicenseScannableInformation>("tests/aamva/secured.jsonld");
+
+    let status_list_client = ConstTerseStatusListProvider::new(
+        StatusLists,
+        StatusListInfo::new(1000, StatusPurpose::Revocation),
+    );
+
+    let params = VerificationParameters::new_with(
+        AnyDidMethod::default().into_vm_resolver(),
+        status_list_client,
+    );
+
+    let (outcome, statuses) = optical_barcode_credential::verify_detailed_with_statuses(
+        &vc,
+        &DL_SUBFILE.mandatory,
+        params,
+    )
+    .await
+    .unwrap();
+    assert_eq!(outcome, VerificationOutcome::Valid);
+    assert_eq!(statuses, [(StatusPurpose::Revocation, 0)])
+}
+
+#[async_std::test]
+async fn aamva_verify_detailed() {
+    let vc = load_signed::<AamvaDriversLicenseScannableInformation>("tests/aamva/secured.jsonld");
+
+    for (purpose, expected) in [
+        (StatusPurpose::Revocation, VerificationOutcome::Revoked),
+        (StatusPurpose::Suspension, VerificationOutcome::Suspended),
+    ] {
+        let status_list_client =
+            ConstTerseStatusListProvider::new(SetStatusLists, StatusListInfo::new(1000, purpose));
+        let params = VerificationParameters::new_with(
+            AnyDidMethod::default().into_vm_resolver(),
+            status_list_client,
+        );
+        let outcome =
+            optical_barcode_credential::verify_detailed(&vc, &DL_SUBFILE.mandatory, params)
+                .await
+                .unwrap();
+        assert_eq!(outcome, expected)
+    }
+
+    // No status list client to check the credential status.
+    let params = VerificationParameters::new(AnyDidMethod::default().into_vm_resolver());
+    let outcome = optical_barcode_credential::verify_detailed(&vc, &DL_SUBFILE.mandatory, params)
+        .await
+        .unwrap();
+    assert_eq!(outcome, VerificationOutcome::StatusUnavailable)
+}
+
+#[async_std::test]
+async fn aamva_verify_with_status() {
+    let vc = load_signed::<AamvaDriversLicenseScannableInformation>("tests/aamva/secured.jsonld");
+
+    let status_list_client = ConstTerseStatusListProvider::new(
+        StatusLists,
+        StatusListInfo::new(1000, StatusPurpose::Message),
+    );
+
+    let params = VerificationParameters::new_with(
+        AnyDidMethod::default().into_vm_resolver(),
+        status_list_client,
+    );
+
+    let (result, messages) =
+        optical_barcode_credential::verify_with_status(&vc, &DL_SUBFILE.mandatory, params)
+            .await
+            .unwrap();
+    assert_eq!(result, Ok(()));
+    assert_eq!(
+        messages,
+        [StatusMessage {
+            entry_index: 0,
+            status: 0
+        }]
+    );
+
+    // Other purposes are not reported as messages.
+    let status_list_client = ConstTerseStatusListProvider::new(
+        StatusLists,
+        StatusListInfo::new(1000, StatusPurpose::Revocation),
+    );
+
+    let params = VerificationParameters::new_with(
+        AnyDidMethod::default().into_vm_resolver(),
+        status_list_client,
+    );
+
+    let (result, messages) =
+        optical_barcode_credential::verify_with_status(&vc, &DL_SUBFILE.mandatory, params)
+            .await
+            .unwrap();
+    assert_eq!(result, Ok(()));
+    assert!(messages.is_empty())
+}
+
+#[async_std::test]
+async fn aamva_status_list_length_mismatch() {
+    let base_url = UriBuf::new(
+        b"https://sandbox.platform.veres.dev/statuses/z19rJ4oGrbFCqf3cNTVDHSbNd/status-lists"
+            .to_vec(),
+    )
+    .unwrap();
+    let entry = TerseBitstringStatusListEntry::new(base_url, 42);
+
+    let provider = ConstTerseStatusListProvider::new(
+        StatusLists,
+        StatusListInfo::new(1000, StatusPurpose::Revocation),
+    );
+    assert_eq!(provider.get_status(&entry).await.unwrap().1, Some(0));
+
+    // The fetched lists have 1000 entries.
+    let provider = ConstTerseStatusListProvider::new(
+        StatusLists,
+        StatusListInfo::new(500, StatusPurpose::Revocation),
+    );
+    assert!(provider.get_status(&entry).await.is_err())
+}
+
+#[test]
+fn aamva_summary() {
+    let vc = load_signed::<AamvaDriversLicenseScannableInformation>("tests/aamva/secured.jsonld");
+    let summary = vc.summary();
+
+    assert_eq!(
+        summary.issuer.as_str(),
+        "did:key:zDnaeWjKfs1ob9QcgasjYSPEMkwq31hmvSAWPVAgnrt1e9GKj"
+    );
+    assert_eq!(
+        summary.subject_type,
+        "AamvaDriversLicenseScannableInformation"
+    );
+    assert!(!summary.protected_fields.as_ref().unwrap().is_empty());
+    assert_eq!(summary.statuses.len(), 1);
+    assert_eq!(summary.created, None);
+
+    let text = summary.to_string();
+    assert!(text.contains("subject type: AamvaDriversLicenseScannableInformation\n"));
+    assert!(text.contains(
+        "status: https://sandbox.platform.veres.dev/statuses/z19rJ4oGrbFCqf3cNTVDHSbNd/status-lists #3851559041\n"
+    ))
+}
+
 #[async_std::test]
 async fn aamva_compress() {
     let vc = load_signed::<AamvaDriversLicenseScannableInformation>("tests/aamva/secured.jsonld");
@@ -78,27 +383,79 @@ async fn aamva_compress() {
     }
 }
 
+#[async_std::test]
+async fn aamva_compress_deterministic() {
+    let vc = load_signed::<AamvaDriversLicenseScannableInformation>("tests/aamva/secured.jsonld");
+    let a = optical_barcode_credential::encode_to_bytes(&vc).await;
+    let b = optical_barcode_credential::encode_to_bytes_checked(&vc)
+        .await
+        .unwrap();
+    assert_eq!(a, b)
+}
+
+#[async_std::test]
+async fn aamva_compression_stats() {
+    let vc = load_signed::<AamvaDriversLicenseScannableInformation>("tests/aamva/secured.jsonld");
+    let stats = optical_barcode_credential::compression_stats(&vc).await;
+
+    // The fixture printed without whitespace, and the specification CBOR-LD
+    // example.
+    assert_eq!(stats.json_len, 890);
+    assert_eq!(stats.cbor_ld_len, 145);
+    assert_eq!(stats.saved(), 745);
+    assert!(stats.ratio() < 0.17)
+}
+
 #[async_std::test]
 async fn aamva_decompress() {
     let input = hex::decode("d90664a60183198000198001198002189d82187618a418b8a3189c18a618ce18b218d01ae592208118baa2189c18a018a8447582002018be18aa18c0a5189c186c18d60418e018e618e258417ab7c2e56b49e2cce62184ce26818e15a8b173164401b5d3bb93ffd6d2b5eb8f6ac0971502ae3dd49d17ec66528164034c912685b8111bc04cdc9ec13dbadd91cc18e418ac").unwrap();
-    let output = json_syntax::to_value(
-        optical_barcode_credential::decode_from_bytes::<AamvaDriversLicenseScannableInformation>(
-            &input,
-        )
-        .await
-        .unwrap(),
-    )
-    .unwrap();
-    let expected = json_syntax::to_value(load_signed::<AamvaDriversLicenseScannableInformation>(
-        "tests/aamva/secured.jsonld",
-    ))
+    let output = optical_barcode_credential::decode_from_bytes::<
+        AamvaDriversLicenseScannableInformation,
+    >(&input)
+    .await
     .unwrap();
-    if output != expected {
-        eprintln!("output: {}", output.pretty_print());
+    let expected =
+        load_signed::<AamvaDriversLicenseScannableInformation>("tests/aamva/secured.jsonld");
+    if !output.semantically_eq(&expected) {
+        eprintln!(
+            "output: {}",
+            json_syntax::to_value(output).unwrap().pretty_print()
+        );
         panic!("invalid decompression")
     }
 }
 
+#[async_std::test]
+async fn aamva_decompress_subject_type_mismatch() {
+    let vc = load_signed::<AamvaDriversLicenseScannableInformation>("tests/aamva/secured.jsonld");
+    let bytes = optical_barcode_credential::encode_to_bytes(&vc).await;
+
+    let error = optical_barcode_credential::decode_from_bytes::<MachineReadableZone>(&bytes)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(
+        error,
+        DecodeError::SubjectTypeMismatch {
+            expected: "MachineReadableZone",
+            ref found
+        } if found == "AamvaDriversLicenseScannableInformation"
+    ))
+}
+
+#[async_std::test]
+async fn aamva_decode_any() {
+    let vc = load_signed::<AamvaDriversLicenseScannableInformation>("tests/aamva/secured.jsonld");
+    let bytes = optical_barcode_credential::encode_to_bytes(&vc).await;
+    let decoded = optical_barcode_credential::decode_any_from_bytes(&bytes)
+        .await
+        .unwrap();
+    assert!(matches!(
+        decoded,
+        optical_barcode_credential::DecodedVcb::Aamva(_)
+    ))
+}
+
 const PDF417_PAYLOAD: &str = "@\n\x1e\rANSI 000000090002DL00410234ZZ02750202DLDAQF987654321\nDCSSMITH\nDDEN\nDACJOHN\nDDFN\nDADNONE\nDDGN\nDCAC\nDCBNONE\nDCDNONE\nDBD01012024\nDBB04191988\nDBA04192030\nDBC1\nDAU069 IN\nDAYBRO\nDAG123 MAIN ST\nDAIANYVILLE\nDAJUTO\nDAKF87P20000  \nDCFUTODOCDISCRIM\nDCGUTO\nDAW158\nDCK1234567890\nDDAN\rZZZZA2QZkpgGDGYAAGYABGYACGJ2CGHYYpBi4oxicGKYYzhiyGNAa5ZIggRi6ohicGKAYqER1ggAgGL4YqhjApRicGGwY1gQY4BjmGOJYQXq3wuVrSeLM5iGEziaBjhWosXMWRAG107uT_9bSteuPasCXFQKuPdSdF-xmUoFkA0yRJoW4ERvATNyewT263ZHMGOQYrA==\r";
 
 #[async_std::test]
@@ -136,3 +493,459 @@ async fn aamva_pdf417_payload_encode() {
 
     assert_eq!(bytes, PDF417_PAYLOAD.as_bytes())
 }
+
+#[async_std::test]
+async fn aamva_pdf417_duplicate_subfile() {
+    let vc = load_signed::<AamvaDriversLicenseScannableInformation>("tests/aamva/secured.jsonld");
+
+    let mut file = pdf_417::FileBuilder::new(0, 9, 0);
+    file.push_dl(DL_SUBFILE.clone());
+    file.push_zz(ZZSubfile::encode_credential(&vc).await);
+    assert_eq!(file.into_bytes(), PDF417_PAYLOAD.as_bytes());
+
+    let mut file = pdf_417::FileBuilder::new(0, 9, 0);
+    file.push_dl(DL_SUBFILE.clone());
+    file.push_dl(DL_SUBFILE.clone());
+    let error = file.write(&mut Vec::new()).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput)
+}
+
+#[async_std::test]
+async fn aamva_pdf417_payload_encode_streaming() {
+    let vc = load_signed::<AamvaDriversLicenseScannableInformation>("tests/aamva/secured.jsonld");
+    let zz = pdf_417::Subfile::from(ZZSubfile::encode_credential(&vc).await);
+
+    let mut file = pdf_417::FileBuilder::new(0, 9, 0);
+    file.push_dl(DL_SUBFILE.clone());
+
+    let mut bytes = Vec::new();
+    let mut writer = file
+        .write_streaming(&mut bytes, &[(*b"ZZ", zz.data.len() as u64)])
+        .unwrap();
+    assert_eq!(writer.begin_subfile().unwrap(), *b"ZZ");
+    for chunk in zz.data.chunks(16) {
+        writer.write_all(chunk).unwrap();
+    }
+    assert!(writer.write_all(b"x").is_err());
+    writer.finish().unwrap();
+
+    assert_eq!(bytes, PDF417_PAYLOAD.as_bytes())
+}
+
+enum AnySubfile {
+    Dl(DlSubfile),
+    Zz(ZZSubfile),
+}
+
+impl pdf_417::DecodeSubfile for AnySubfile {
+    fn decode_subfile(reader: &mut impl std::io::BufRead) -> std::io::Result<Self> {
+        let subfile_type = reader.fill_buf()?.get(..2).map(|t| [t[0], t[1]]);
+        match subfile_type {
+            Some(t) if t == *b"DL" => DlSubfile::decode_subfile(reader).map(Self::Dl),
+            Some(t) if t == *b"ZZ" => ZZSubfile::decode_subfile(reader).map(Self::Zz),
+            _ => Err(std::io::ErrorKind::InvalidData.into()),
+        }
+    }
+}
+
+#[async_std::test]
+async fn aamva_pdf417_payload_sequential() {
+    // `&[u8]` does not implement `Seek`.
+    let mut reader = PDF417_PAYLOAD.as_bytes();
+    let mut file = pdf_417::File::new(&mut reader).unwrap();
+    let mut subfiles = file
+        .read_subfiles_sequential::<AnySubfile>(&[*b"ZZ", *b"DL"])
+        .unwrap();
+
+    let Some(AnySubfile::Dl(dl)) = subfiles.remove(b"DL") else {
+        panic!("missing DL subfile")
+    };
+    let Some(AnySubfile::Zz(zz)) = subfiles.remove(b"ZZ") else {
+        panic!("missing ZZ subfile")
+    };
+
+    let vc = zz.decode_credential().await.unwrap();
+    let params = VerificationParameters::new_with(
+        AnyDidMethod::default().into_vm_resolver(),
+        ConstTerseStatusListProvider::new(
+            StatusLists,
+            StatusListInfo::new(1000, StatusPurpose::Revocation),
+        ),
+    );
+
+    let result = verify(&vc, &dl.mandatory, params).await.unwrap();
+    assert_eq!(result, Ok(()))
+}
+
+#[async_std::test]
+async fn aamva_pdf417_barcode_source() {
+    let bytes = Pdf417Payload(PDF417_PAYLOAD.as_bytes())
+        .read_payload()
+        .unwrap();
+    let vc =
+        optical_barcode_credential::decode_from_bytes::<AamvaDriversLicenseScannableInformation>(
+            &bytes,
+        )
+        .await
+        .unwrap();
+    let expected =
+        load_signed::<AamvaDriversLicenseScannableInformation>("tests/aamva/secured.jsonld");
+    assert!(vc.semantically_eq(&expected))
+}
+
+#[test]
+fn aamva_pdf417_payload_checked() {
+    let mut cursor = Cursor::new(PDF417_PAYLOAD);
+    pdf_417::File::new_checked(&mut cursor).unwrap();
+
+    let truncated = &PDF417_PAYLOAD[..PDF417_PAYLOAD.len() - 1];
+    let mut cursor = Cursor::new(truncated);
+    let error = pdf_417::File::new_checked(&mut cursor).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData)
+}
+
+#[test]
+fn aamva_pdf417_subfile_bytes() {
+    let mut cursor = Cursor::new(PDF417_PAYLOAD);
+    let mut file = pdf_417::File::new(&mut cursor).unwrap();
+    let index = file.index_of(b"DL").unwrap();
+    let bytes = file.subfile_bytes(index).unwrap();
+    assert_eq!(bytes.len(), 234);
+    assert!(bytes.starts_with(b"DLDAQ"));
+
+    let decoded = DlSubfile::decode_subfile_from_bytes(&bytes).unwrap();
+    let dl: DlSubfile = file.read_subfile(b"DL").unwrap().unwrap();
+    assert_eq!(decoded.mandatory, dl.mandatory);
+
+    assert!(file.subfile_bytes(2).is_err())
+}
+
+#[test]
+fn aamva_dl_subfile_diff() {
+    assert!(DL_SUBFILE.diff(&DL_SUBFILE).is_empty());
+
+    let mut other = DL_SUBFILE.clone();
+    other
+        .mandatory
+        .set(DlMandatoryElement::CustomerFamilyName, b"DOE".to_vec());
+    other.optional.set(DlOptionalElement::WeightInPounds, None);
+    other
+        .optional
+        .set(DlOptionalElement::HairColor, Some(b"BRO".to_vec()));
+
+    assert_eq!(
+        DL_SUBFILE.diff(&other),
+        [
+            FieldDiff::Changed {
+                element: DlElement::Mandatory(DlMandatoryElement::CustomerFamilyName),
+                old: b"SMITH".to_vec(),
+                new: b"DOE".to_vec()
+            },
+            FieldDiff::Removed {
+                element: DlElement::Optional(DlOptionalElement::WeightInPounds),
+                value: b"158".to_vec()
+            },
+            FieldDiff::Added {
+                element: DlElement::Optional(DlOptionalElement::HairColor),
+                value: b"BRO".to_vec()
+            }
+        ]
+    )
+}
+
+#[test]
+fn aamva_pdf417_builder_from_file() {
+    let mut cursor = Cursor::new(PDF417_PAYLOAD);
+    let mut file = pdf_417::File::new(&mut cursor).unwrap();
+    let builder = pdf_417::FileBuilder::from_file(&mut file).unwrap();
+    assert_eq!(builder.into_bytes(), PDF417_PAYLOAD.as_bytes());
+
+    let mut cursor = Cursor::new(PDF417_PAYLOAD);
+    let mut file = pdf_417::File::new(&mut cursor).unwrap();
+    let mut builder = pdf_417::FileBuilder::from_file(&mut file).unwrap();
+    builder.push(pdf_417::Subfile::new(*b"ZU", b"ZUAX\r".to_vec()));
+    let bytes = builder.into_bytes();
+
+    let mut cursor = Cursor::new(bytes.as_slice());
+    let mut file = pdf_417::File::new_checked(&mut cursor).unwrap();
+    assert_eq!(file.subfile_count(), 3);
+    assert_eq!(file.subfile_bytes(2).unwrap(), b"ZUZUAX\r");
+
+    let dl: DlSubfile = file.read_subfile(b"DL").unwrap().unwrap();
+    assert_eq!(dl.mandatory, DL_SUBFILE.mandatory)
+}
+
+#[test]
+fn aamva_pdf417_payload_lenient() {
+    for declared in ["01", "03"] {
+        let payload = PDF417_PAYLOAD.replacen("0002DL", &format!("00{declared}DL"), 1);
+
+        let mut cursor = Cursor::new(payload.as_bytes());
+        assert!(pdf_417::File::new(&mut cursor)
+            .and_then(|mut file| file.read_subfile::<ZZSubfile>(b"ZZ"))
+            .map_or(true, |zz| zz.is_none()));
+
+        let mut cursor = Cursor::new(payload.as_bytes());
+        let mut file = pdf_417::File::new_lenient(&mut cursor).unwrap();
+        assert_eq!(file.header().entry_count, declared.parse().unwrap());
+        assert_eq!(file.subfile_count(), 2);
+        assert!(file.read_subfile::<DlSubfile>(b"DL").unwrap().is_some());
+        assert!(file.read_subfile::<ZZSubfile>(b"ZZ").unwrap().is_some())
+    }
+
+    // Designators straddling the reader buffer boundary.
+    let mut reader = BufReader::with_capacity(4, Cursor::new(PDF417_PAYLOAD.as_bytes()));
+    let mut file = pdf_417::File::new_lenient(&mut reader).unwrap();
+    assert_eq!(file.subfile_count(), 2);
+    assert!(file.read_subfile::<ZZSubfile>(b"ZZ").unwrap().is_some());
+
+    // Garbage between the designators and the data, read as a designator.
+    let payload = PDF417_PAYLOAD
+        .replacen("0002DL0041", "0002DL0051", 1)
+        .replacen("ZZ02750202DL", "ZZ02850202garbage...DL", 1);
+    for capacity in [4, 64] {
+        let mut reader = BufReader::with_capacity(capacity, Cursor::new(payload.as_bytes()));
+        let mut file = pdf_417::File::new_lenient(&mut reader).unwrap();
+        assert_eq!(file.subfile_count(), 2);
+
+        let subfiles = file
+            .read_subfiles_sequential::<AnySubfile>(&[*b"DL", *b"ZZ"])
+            .unwrap();
+        assert_eq!(subfiles.len(), 2)
+    }
+}
+
+#[test]
+fn aamva_crlf_segment_terminator() {
+    let crlf = DL_SUBFILE_BYTES.replace('\r', "\r\n").repeat(2);
+
+    let mut cursor = Cursor::new(crlf.as_bytes());
+    for _ in 0..2 {
+        let subfile = DlSubfile::decode_subfile(&mut cursor).unwrap();
+        assert_eq!(subfile.mandatory, DL_SUBFILE.mandatory);
+    }
+
+    let mut cursor = Cursor::new(crlf.as_bytes());
+    pdf_417::Record::decode_subfile_strict(&mut cursor).unwrap();
+    assert_eq!(crlf.as_bytes()[cursor.position() as usize], b'\n');
+
+    let borrowed = DlSubfile::borrowed(crlf.as_bytes()).unwrap();
+    assert_eq!(borrowed.len(), DL_SUBFILE.len())
+}
+
+#[test]
+fn aamva_subfile_parse_error_offset() {
+    let malformed = DL_SUBFILE_BYTES.replace("123 MAIN", "123\x1eMAIN");
+    let error = DlSubfile::decode_subfile_from_bytes(malformed.as_bytes())
+        .err()
+        .unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+
+    let offset = DL_SUBFILE_BYTES.find("123 MAIN").unwrap() + 3;
+    assert_eq!(
+        error.to_string(),
+        format!("unexpected record separator at offset {offset} while reading field DAG")
+    );
+
+    let mut cursor = Cursor::new(&malformed.as_bytes()[2..]);
+    let mut offset = 2;
+    RecordEntry::decode_at(&mut cursor, &mut offset).unwrap();
+    RecordEntry::decode_at(&mut cursor, &mut offset).unwrap();
+    let error = RecordEntry::decode_at(&mut cursor, &mut offset).unwrap_err();
+    assert_eq!(
+        error.kind,
+        pdf_417::SubfileParseErrorKind::UnexpectedRecordSeparator
+    );
+    assert_eq!(error.field, Some(*b"DAG"))
+}
+
+#[test]
+fn aamva_subfile_try_from() {
+    let subfile = pdf_417::Subfile::from(DL_SUBFILE.clone());
+    let dl = DlSubfile::try_from(&subfile).unwrap();
+    assert_eq!(dl.mandatory, DL_SUBFILE.mandatory);
+
+    let mismatch = pdf_417::Subfile::new(*b"ID", subfile.data.clone());
+    let error = DlSubfile::try_from(&mismatch).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+
+    let zz = pdf_417::Subfile::from(ZZSubfile {
+        zza: "AAAA".to_owned(),
+    });
+    assert_eq!(ZZSubfile::try_from(&zz).unwrap().zza, "AAAA")
+}
+
+#[test]
+fn aamva_pdf417_legacy_header() {
+    let bytes = b"@\n\x1e\rAAMVA000000000001";
+    let header = pdf_417::Header::decode(&mut Cursor::new(bytes)).unwrap();
+    assert_eq!(
+        header.compliance_indicator,
+        pdf_417::ComplianceIndicator::Aamva
+    );
+
+    header.validate().unwrap();
+
+    let mut encoded = Vec::new();
+    header.encode(&mut encoded).unwrap();
+    assert_eq!(encoded, bytes)
+}
+
+#[test]
+fn aamva_pdf417_header_checked() {
+    let header = pdf_417::Header::decode_checked(&mut Cursor::new(PDF417_PAYLOAD)).unwrap();
+    assert_eq!(header.entry_count, 2);
+
+    for bytes in [
+        b"@\n\x1e\rANSI 000000110002",
+        b"@\n\x1e\rANSI 000000000002",
+        b"@\n\x1e\rAAMVA000000090002",
+        b"@\n\x1e\rANSI 000000090000",
+        b"@\n\x1e\rANSI 000000090099",
+    ] {
+        let error = pdf_417::Header::decode_checked(&mut Cursor::new(bytes))
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData)
+    }
+}
+
+#[test]
+fn aamva_pdf417_card_design_profile() {
+    let header = pdf_417::Header::decode(&mut Cursor::new(PDF417_PAYLOAD)).unwrap();
+    assert_eq!(
+        header.card_design_profile(),
+        pdf_417::CardDesignProfile::Standard {
+            version: 9,
+            year: 2016
+        }
+    );
+    assert!(header.is_newer_than(8, 5));
+    assert!(!header.is_newer_than(9, 0));
+    assert!(!header.is_newer_than(9, 1));
+
+    assert_eq!(
+        pdf_417::CardDesignProfile::from_version(0),
+        pdf_417::CardDesignProfile::Legacy
+    );
+    assert!(!pdf_417::CardDesignProfile::from_version(11).is_known())
+}
+
+#[test]
+fn aamva_mandatory_elements_iter() {
+    let elements: Vec<_> = DL_SUBFILE.mandatory.clone().into_iter().collect();
+    assert_eq!(elements.len(), DlMandatoryElement::COUNT);
+    assert_eq!(
+        DlMandatoryElements::try_from_iter(elements.clone()).unwrap(),
+        DL_SUBFILE.mandatory
+    );
+
+    let partial = elements
+        .into_iter()
+        .filter(|(e, _)| *e != DlMandatoryElement::CustomerFamilyName);
+    let error = DlMandatoryElements::try_from_iter(partial.clone()).unwrap_err();
+    assert_eq!(error.0, DlMandatoryElement::CustomerFamilyName);
+
+    let defaulted: DlMandatoryElements = partial.collect();
+    assert!(defaulted.customer_family_name.is_empty())
+}
+
+#[test]
+fn aamva_element_codes() {
+    assert_eq!(
+        DlElement::from_code("DAC"),
+        Some(DlElement::Mandatory(DlMandatoryElement::CustomerFirstName))
+    );
+    assert_eq!(
+        DlElement::from_code("DAH"),
+        Some(DlElement::Optional(DlOptionalElement::AddressStreet2))
+    );
+    assert_eq!(DlElement::from_code("DA"), None);
+    assert_eq!(DlElement::from_code("XYZ"), None);
+
+    let e = DlElement::Mandatory(DlMandatoryElement::CustomerFamilyName);
+    assert_eq!(e.to_string(), "DCS");
+    assert_eq!(DlElement::from_code(&e.to_string()), Some(e))
+}
+
+#[test]
+fn aamva_typed_accessors() {
+    let date_of_birth = DL_SUBFILE.mandatory.date_of_birth().unwrap();
+    assert_eq!(date_of_birth.as_str(), "04191988");
+
+    let weight = DL_SUBFILE.optional.weight_in_pounds().unwrap().unwrap();
+    assert_eq!(weight.as_str(), "158");
+
+    let mut dl = DL_SUBFILE.clone();
+    dl.mandatory.date_of_birth = b"1988-04-19".to_vec();
+    assert!(dl.mandatory.date_of_birth().is_err())
+}
+
+#[async_std::test]
+async fn aamva_credentials_subfile() {
+    let aamva =
+        load_signed::<AamvaDriversLicenseScannableInformation>("tests/aamva/secured.jsonld");
+    let mrz = load_signed::<MachineReadableZone>("tests/mrz/secured.jsonld");
+
+    let mut credentials = CredentialsSubfile::new();
+    credentials.push_credential(&aamva).await;
+    credentials.push_credential(&mrz).await;
+
+    let subfile = pdf_417::Subfile::try_from(credentials).unwrap();
+    let decoded: CredentialsSubfile = subfile.decode().unwrap();
+    assert!(matches!(
+        decoded.decode_credentials().await.unwrap().as_slice(),
+        [DecodedVcb::Aamva(_), DecodedVcb::MachineReadableZone(_)]
+    ));
+
+    let zz = ZZSubfile::encode_credential(&aamva).await;
+    let decoded: CredentialsSubfile = pdf_417::Subfile::from(zz.clone()).decode().unwrap();
+    assert_eq!(decoded.credentials, [zz.zza]);
+
+    assert!(pdf_417::Subfile::try_from(CredentialsSubfile::new()).is_err())
+}
+
+#[async_std::test]
+async fn aamva_zz_decode_unpadded() {
+    let vc = load_signed::<AamvaDriversLicenseScannableInformation>("tests/aamva/secured.jsonld");
+    let padded = ZZSubfile::encode_credential(&vc).await;
+    assert!(padded.zza.ends_with('='));
+
+    let unpadded = ZZSubfile {
+        zza: padded.zza.trim_end_matches('=').to_owned(),
+    };
+    let decoded = unpadded.decode_credential().await.unwrap();
+    assert!(decoded.semantically_eq(&vc))
+}
+
+#[async_std::test]
+async fn aamva_zz_decode_errors() {
+    let zz = ZZSubfile { zza: String::new() };
+    assert!(matches!(
+        zz.decode_credential().await,
+        Err(ZZDecodeError::Empty)
+    ));
+
+    let vc = load_signed::<AamvaDriversLicenseScannableInformation>("tests/aamva/secured.jsonld");
+    let mut zz = ZZSubfile::encode_credential(&vc).await;
+    zz.zza.truncate(zz.zza.len() - 3);
+    let len = zz.zza.len();
+    assert!(matches!(
+        zz.decode_credential().await,
+        Err(ZZDecodeError::InvalidBase64 { position }) if position == len
+    ));
+
+    let zz = ZZSubfile {
+        zza: "AAAA*AAA".to_owned(),
+    };
+    assert!(matches!(
+        zz.decode_credential().await,
+        Err(ZZDecodeError::InvalidBase64 { position: 4 })
+    ));
+
+    let zz = ZZSubfile {
+        zza: "AAAA".to_owned(),
+    };
+    let error = zz.decode_credential().await.err().unwrap();
+    assert!(error.is_cbor_ld() && !error.is_empty() && !error.is_invalid_base64())
+}
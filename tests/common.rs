@@ -34,6 +34,20 @@ where
     json_syntax::from_value(json).unwrap()
 }
 
+/// Returns the `proofValue` of the first proof.
+pub fn proof_value<T>(vc: &DataIntegrity<OpticalBarcodeCredential<T>, EcdsaXi2023>) -> String
+where
+    T: OpticalBarcodeCredentialSubject,
+{
+    let json = json_syntax::to_value(vc).unwrap();
+    json.as_object()
+        .and_then(|o| o.get_unique("proof").ok().flatten())
+        .and_then(|p| p.as_object()?.get_unique("proofValue").ok().flatten())
+        .and_then(|v| v.as_str())
+        .unwrap()
+        .to_owned()
+}
+
 pub fn load_proof_configuration(path: impl AsRef<Path>) -> ProofConfiguration<EcdsaXi2023> {
     let content = fs::read_to_string(path).unwrap();
     let json = json_syntax::Value::parse_str(&content).unwrap().0;
@@ -52,3 +66,16 @@ impl TypedStatusMapProvider<Uri, BitstringStatusListCredential> for StatusLists
         )))
     }
 }
+
+/// Status lists with every status set.
+pub struct SetStatusLists;
+
+impl TypedStatusMapProvider<Uri, BitstringStatusListCredential> for SetStatusLists {
+    async fn get_typed(&self, _id: &Uri) -> Result<MaybeCached<StatusList>, ProviderError> {
+        Ok(MaybeCached::NotCached(StatusList::from_bytes(
+            1.try_into().unwrap(),
+            vec![0xffu8; 125],
+            TimeToLive::DEFAULT,
+        )))
+    }
+}
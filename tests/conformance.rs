@@ -0,0 +1,191 @@
+//! Specification worked examples.
+//!
+//! Each directory `tests/conformance/<name>` is a fixture checking the
+//! specification credential `tests/<name>/unsecured.jsonld` and its signed
+//! form `tests/<name>/secured.jsonld`, shared with the other tests, against:
+//!   - `extra-information.txt`: the MRZ lines, or the AAMVA DL subfile bytes;
+//!   - `optical-data.hex`: the expected optical data;
+//!   - `cbor-ld.hex`: the expected compressed credential;
+//!   - `qr-code.txt` (optional): the expected QR code payload;
+//!   - `proof-value.txt` (optional): the expected `proofValue` of the
+//!     credential signed with the [`testing`] key.
+//!
+//! All but the last are copied from the specification. Its examples are
+//! signed with a key whose secret part is not published, so their proofs can
+//! only be checked, not reproduced. With the `testing` feature, the
+//! unsecured credential is also signed with [`testing::sign`]: its optical
+//! data and claims must then match the specification byte for byte, and its
+//! proof, which only depends on the test key, must match `proof-value.txt`.
+//! That value was computed independently of this crate.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use json_syntax::Parse;
+use ssi::dids::{AnyDidMethod, DIDResolver};
+use w3c_vc_barcodes::{
+    aamva::dlid::{pdf_417::DecodeSubfile, DlSubfile},
+    optical_barcode_credential::{
+        self, OpticalBarcodeCredentialSubject, SemanticEq, VerificationParameters,
+    },
+    verify, AamvaDriversLicenseScannableInformation, MachineReadableZone, MrzLines,
+};
+
+mod common;
+pub use common::*;
+
+#[async_std::test]
+async fn conformance() {
+    let mut count = 0;
+
+    for entry in fs::read_dir("tests/conformance").unwrap() {
+        let dir = entry.unwrap().path();
+        if !dir.is_dir() {
+            continue;
+        }
+
+        let secured = secured_credential(&dir);
+        let xi = fs::read(dir.join("extra-information.txt")).unwrap();
+        match subject_type(&secured).as_str() {
+            "MachineReadableZone" => {
+                let mrz = MrzLines::try_from(xi.as_slice()).unwrap().into_mrz();
+                check_fixture::<MachineReadableZone>(&dir, &secured, &mrz).await;
+                #[cfg(feature = "testing")]
+                check_signing::<MachineReadableZone>(&dir, &secured, &mrz).await
+            }
+            "AamvaDriversLicenseScannableInformation" => {
+                let subfile = DlSubfile::decode_subfile_from_bytes(&xi).unwrap();
+                check_fixture::<AamvaDriversLicenseScannableInformation>(
+                    &dir,
+                    &secured,
+                    &subfile.mandatory,
+                )
+                .await;
+                #[cfg(feature = "testing")]
+                check_signing::<AamvaDriversLicenseScannableInformation>(
+                    &dir,
+                    &secured,
+                    &subfile.mandatory,
+                )
+                .await
+            }
+            other => panic!("{}: unknown subject type `{other}`", dir.display()),
+        }
+
+        count += 1;
+    }
+
+    assert!(count > 0, "no conformance fixture")
+}
+
+/// Returns the path of the signed credential checked by the given fixture.
+fn secured_credential(dir: &Path) -> PathBuf {
+    Path::new("tests")
+        .join(dir.file_name().unwrap())
+        .join("secured.jsonld")
+}
+
+fn subject_type(secured: &Path) -> String {
+    let content = fs::read_to_string(secured).unwrap();
+    let json = json_syntax::Value::parse_str(&content).unwrap().0;
+    json.as_object()
+        .and_then(|o| o.get_unique("credentialSubject").ok().flatten())
+        .and_then(|s| s.as_object()?.get_unique("type").ok().flatten())
+        .and_then(|t| t.as_str())
+        .map(ToOwned::to_owned)
+        .unwrap_or_else(|| panic!("{}: missing subject type", secured.display()))
+}
+
+fn read_hex(path: impl AsRef<Path>) -> Vec<u8> {
+    hex::decode(fs::read_to_string(path).unwrap().trim()).unwrap()
+}
+
+async fn check_fixture<T>(dir: &Path, secured: &Path, xi: &T::ExtraInformation)
+where
+    T: OpticalBarcodeCredentialSubject,
+{
+    let name = dir.display();
+    let vc = load_signed::<T>(secured);
+
+    let optical_data = vc.credential_subjects[0].create_optical_data(xi);
+    assert_eq!(
+        optical_data.as_slice(),
+        read_hex(dir.join("optical-data.hex")),
+        "{name}: optical data mismatch"
+    );
+
+    let bytes = optical_barcode_credential::encode_to_bytes(&vc).await;
+    assert_eq!(
+        hex::encode(&bytes),
+        hex::encode(read_hex(dir.join("cbor-ld.hex"))),
+        "{name}: CBOR-LD mismatch"
+    );
+
+    let qr_code = dir.join("qr-code.txt");
+    if qr_code.exists() {
+        assert_eq!(
+            MachineReadableZone::encode_qr_code_payload(&bytes),
+            fs::read_to_string(qr_code).unwrap().trim_end(),
+            "{name}: QR code payload mismatch"
+        );
+    }
+
+    let decoded = optical_barcode_credential::decode_from_bytes::<T>(&bytes)
+        .await
+        .unwrap();
+    assert!(decoded.semantically_eq(&vc), "{name}: decoding mismatch");
+
+    let params =
+        VerificationParameters::new(AnyDidMethod::default().into_vm_resolver()).ignore_status(true);
+    let result = verify(&vc, xi, params).await.unwrap();
+    assert_eq!(result, Ok(()), "{name}: verification failed")
+}
+
+/// Signs the specification's unsecured credential with the test key.
+#[cfg(feature = "testing")]
+async fn check_signing<T>(dir: &Path, secured: &Path, xi: &T::ExtraInformation)
+where
+    T: OpticalBarcodeCredentialSubject,
+{
+    use ssi::claims::data_integrity::DataIntegrity;
+    use w3c_vc_barcodes::testing;
+
+    let name = dir.display();
+    let unsecured = load_unsigned::<T>(secured.with_file_name("unsecured.jsonld"));
+    let vc = testing::sign(unsecured, xi).await.unwrap();
+
+    let optical_data = vc.credential_subjects[0].create_optical_data(xi);
+    assert_eq!(
+        optical_data.as_slice(),
+        read_hex(dir.join("optical-data.hex")),
+        "{name}: signed optical data mismatch"
+    );
+
+    let proof_value = dir.join("proof-value.txt");
+    if proof_value.exists() {
+        assert_eq!(
+            proof_value(&vc),
+            fs::read_to_string(proof_value).unwrap().trim_end(),
+            "{name}: proof value mismatch"
+        );
+    }
+
+    let params =
+        VerificationParameters::new(AnyDidMethod::default().into_vm_resolver()).ignore_status(true);
+    let result = verify(&vc, xi, params).await.unwrap();
+    assert_eq!(
+        result,
+        Ok(()),
+        "{name}: signed credential verification failed"
+    );
+
+    // Only the proof differs from the specification example.
+    let spec = load_signed::<T>(secured);
+    let claims = DataIntegrity::new(vc.claims, spec.proofs);
+    assert_eq!(
+        hex::encode(optical_barcode_credential::encode_to_bytes(&claims).await),
+        hex::encode(read_hex(dir.join("cbor-ld.hex"))),
+        "{name}: signed claims mismatch"
+    )
+}
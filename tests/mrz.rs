@@ -1,12 +1,34 @@
-use json_syntax::Print;
+use iref::{Iri, Uri};
+use json_syntax::{Parse, Print};
+use proptest::prelude::*;
+use sha2::{Digest, Sha256};
 use ssi::{
-    dids::{AnyDidMethod, DIDResolver},
-    verification_methods::SingleSecretSigner,
+    claims::{
+        vc::{syntax::RequiredContext, v2::V2},
+        ProofValidationError,
+    },
+    dids::{AnyDidMethod, DIDKey, DIDResolver},
+    verification_methods::{
+        Multikey, ReferenceOrOwnedRef, ResolutionOptions, SingleSecretSigner,
+        VerificationMethodResolutionError, VerificationMethodResolver,
+    },
     JWK,
 };
+use std::{
+    borrow::Cow,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
 use w3c_vc_barcodes::{
-    optical_barcode_credential::{self, SignatureParameters, VerificationParameters},
-    verify, MachineReadableZone, MRZ,
+    ecdsa_xi_2023,
+    mrz::{multibase45_decode, multibase45_encode, InvalidMrz, InvalidMrzField},
+    optical_barcode_credential::{
+        self, CachingVmResolver, OpticalBarcodeCredentialSubject, PortraitBinding, ProofInfo,
+        SemanticEq, SignatureParameters, UtopiaV2, ValidateStructure, VerificationOutcome,
+        VerificationParameters, CONTEXT_LOADER,
+    },
+    source::{BarcodeSource, QrCodePayload},
+    verify, DateTime, DidKeyResolver, MachineReadableZone, MrzBuilder, MrzLines, Utc, MRZ,
 };
 
 mod common;
@@ -37,6 +59,129 @@ async fn mrz_sign() {
         .unwrap();
 }
 
+#[async_std::test]
+async fn mrz_sign_proof_options_preset() {
+    let input = load_unsigned::<MachineReadableZone>("tests/mrz/unsecured.jsonld");
+
+    let jwk = JWK::generate_p256();
+    let vm = DIDKey::generate_url(&jwk).unwrap();
+    let created: DateTime<Utc> = "2024-01-01T00:00:00Z".parse().unwrap();
+    let options = ecdsa_xi_2023::proof_options_with(vm.into_iri(), Some(created), None);
+
+    let params = SignatureParameters::new(
+        AnyDidMethod::default().into_vm_resolver(),
+        SingleSecretSigner::new(jwk),
+        None,
+    );
+
+    let vc = optical_barcode_credential::sign(input, &DATA, options, params)
+        .await
+        .unwrap();
+    let proof_created: DateTime<Utc> = vc.proofs[0].created.unwrap().into();
+    assert_eq!(proof_created, created);
+
+    let params = VerificationParameters::new(AnyDidMethod::default().into_vm_resolver());
+    let result = verify(&vc, &DATA, params).await.unwrap();
+    assert_eq!(result, Ok(()))
+}
+
+#[async_std::test]
+async fn mrz_sign_pinned_proof_options() {
+    let input = load_unsigned::<MachineReadableZone>("tests/mrz/unsecured.jsonld");
+    let options = load_proof_configuration("tests/mrz/configuration.jsonld").into_options();
+
+    let created: DateTime<Utc> = "2024-01-01T00:00:00Z".parse().unwrap();
+    let params = SignatureParameters::new(
+        AnyDidMethod::default().into_vm_resolver(),
+        SingleSecretSigner::new(JWK::generate_p256()),
+        None,
+    )
+    .with_created(created)
+    .with_domain("example.org".to_owned())
+    .with_challenge("abc123".to_owned());
+
+    let vc = optical_barcode_credential::sign(input, &DATA, options, params)
+        .await
+        .unwrap();
+
+    let proof = &vc.proofs[0];
+    let proof_created: DateTime<Utc> = proof.created.unwrap().into();
+    assert_eq!(proof_created, created);
+    assert_eq!(proof.domains, ["example.org"]);
+    assert_eq!(proof.challenge.as_deref(), Some("abc123"))
+}
+
+#[test]
+fn mrz_lines() {
+    let raw = DATA
+        .map(|line| String::from_utf8(line.to_vec()).unwrap())
+        .join("\r\n");
+    let lines = MrzLines::try_from(raw.as_bytes()).unwrap();
+    assert_eq!(lines.into_mrz(), DATA);
+
+    let contiguous = DATA.concat();
+    assert_eq!(*MrzLines::try_from(contiguous.as_slice()).unwrap(), DATA);
+
+    let mut invalid = DATA.map(|line| String::from_utf8(line.to_vec()).unwrap());
+    invalid[1].replace_range(4..5, "x");
+    assert_eq!(
+        MrzLines::try_from([
+            invalid[0].as_str(),
+            invalid[1].as_str(),
+            invalid[2].as_str()
+        ]),
+        Err(InvalidMrz::InvalidCharacter {
+            line: 2,
+            column: 5,
+            byte: b'x'
+        })
+    );
+
+    assert_eq!(
+        MrzLines::try_from(&raw.as_bytes()[1..]),
+        Err(InvalidMrz::LineLength { line: 1, len: 29 })
+    );
+}
+
+/// ICAO 9303 part 5 TD1 example.
+#[test]
+fn mrz_builder() {
+    let builder = MrzBuilder::new()
+        .document_code("I")
+        .issuing_state("UTO")
+        .document_number("D23145890")
+        .birth_date("740812")
+        .sex(b'F')
+        .expiry_date("120415")
+        .nationality("UTO")
+        .name("ERIKSSON", "ANNA MARIA");
+
+    assert_eq!(
+        builder.build().unwrap(),
+        [
+            *b"I<UTOD231458907<<<<<<<<<<<<<<<",
+            *b"7408122F1204159UTO<<<<<<<<<<<6",
+            *b"ERIKSSON<<ANNA<MARIA<<<<<<<<<<",
+        ]
+    );
+
+    assert_eq!(
+        builder.clone().document_number("D231458901").build(),
+        Err(InvalidMrzField::Overflow {
+            field: "document number",
+            max: 9
+        })
+    );
+    assert_eq!(
+        builder.clone().birth_date("7408").build(),
+        Err(InvalidMrzField::InvalidDate("date of birth"))
+    );
+    assert_eq!(
+        builder.name("", "").build(),
+        Err(InvalidMrzField::Missing("name"))
+    )
+}
+
 #[async_std::test]
 async fn mrz_verify() {
     let vc = load_signed::<MachineReadableZone>("tests/mrz/secured.jsonld");
@@ -47,6 +192,379 @@ async fn mrz_verify() {
     assert_eq!(result, Ok(()))
 }
 
+#[async_std::test]
+async fn mrz_verify_did_key_resolver() {
+    let vc = load_signed::<MachineReadableZone>("tests/mrz/secured.jsonld");
+
+    let params = VerificationParameters::new(DidKeyResolver);
+
+    let result = verify(&vc, &DATA, params).await.unwrap();
+    assert_eq!(result, Ok(()));
+
+    let id = Iri::new("did:key:zDnaeZSD9XcuULaS8qmgDUa6TMg2QjF9xABnZK42awDH3BEzj#key-1").unwrap();
+    assert!(DidKeyResolver::resolve(id).is_err());
+
+    // secp256k1 key.
+    let id = Iri::new("did:key:zQ3shokFTS3brHcDQrn82RUDfCZESWL1ZdCEJwekUDPQiYBme#zQ3shokFTS3brHcDQrn82RUDfCZESWL1ZdCEJwekUDPQiYBme").unwrap();
+    assert!(DidKeyResolver::resolve(id).is_err())
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn mrz_verify_blocking() {
+    let vc = load_signed::<MachineReadableZone>("tests/mrz/secured.jsonld");
+
+    let params = VerificationParameters::new(DidKeyResolver);
+
+    let result = optical_barcode_credential::verify_blocking(&vc, &DATA, params).unwrap();
+    assert_eq!(result, Ok(()))
+}
+
+/// The specification only publishes P-256 examples. `secured-p384.jsonld`
+/// was hence signed outside of this crate, by an independent implementation
+/// of `ecdsa-xi-2023` (RDFC canonicalization, SHA-384 and the Python
+/// `cryptography` ECDSA signer) first checked against the specification's
+/// P-256 example. Interoperability with other P-384 issuers remains unchecked.
+#[async_std::test]
+async fn mrz_verify_p384() {
+    let vc = load_signed::<MachineReadableZone>("tests/mrz/secured-p384.jsonld");
+
+    let key = DidKeyResolver::resolve(vc.verification_method_id().unwrap()).unwrap();
+    assert_eq!(
+        ecdsa_xi_2023::Curve::of(&key).unwrap(),
+        ecdsa_xi_2023::Curve::P384
+    );
+
+    let params = VerificationParameters::new(AnyDidMethod::default().into_vm_resolver());
+    let result = verify(&vc, &DATA, params).await.unwrap();
+    assert_eq!(result, Ok(()));
+
+    let mut other = DATA;
+    other[2][0] = b'X';
+    let params = VerificationParameters::new(AnyDidMethod::default().into_vm_resolver());
+    let result = verify(&vc, &other, params).await.unwrap();
+    assert!(result.is_err())
+}
+
+#[test]
+fn mrz_verification_method_id() {
+    let vc = load_signed::<MachineReadableZone>("tests/mrz/secured.jsonld");
+    assert_eq!(
+        vc.verification_method_id().unwrap().as_str(),
+        "did:key:zDnaeZSD9XcuULaS8qmgDUa6TMg2QjF9xABnZK42awDH3BEzj#zDnaeZSD9XcuULaS8qmgDUa6TMg2QjF9xABnZK42awDH3BEzj"
+    )
+}
+
+#[async_std::test]
+async fn mrz_verify_detailed() {
+    let vc = load_signed::<MachineReadableZone>("tests/mrz/secured.jsonld");
+
+    let params = VerificationParameters::new(AnyDidMethod::default().into_vm_resolver());
+    let outcome = optical_barcode_credential::verify_detailed(&vc, &DATA, params)
+        .await
+        .unwrap();
+    assert_eq!(outcome, VerificationOutcome::Valid);
+
+    let params = VerificationParameters::new(AnyDidMethod::default().into_vm_resolver());
+    let outcome =
+        optical_barcode_credential::verify_detailed_from_optical_data(&vc, [0u8; 32], params)
+            .await
+            .unwrap();
+    assert_eq!(outcome, VerificationOutcome::SignatureInvalid)
+}
+
+#[async_std::test]
+async fn mrz_optical_data_hash() {
+    let input = load_unsigned::<MachineReadableZone>("tests/mrz/unsecured.jsonld");
+    let optical_data = MachineReadableZone {}.create_optical_data(&DATA);
+    let hash = Sha256::digest(optical_data);
+
+    let jwk = JWK::generate_p256();
+    let vm = DIDKey::generate_url(&jwk).unwrap();
+    let params = SignatureParameters::new(
+        AnyDidMethod::default().into_vm_resolver(),
+        SingleSecretSigner::new(jwk),
+        None,
+    );
+
+    let vc = optical_barcode_credential::sign_from_optical_data_hash(
+        input,
+        hash.as_slice(),
+        ecdsa_xi_2023::proof_options(vm.into_iri()),
+        params,
+    )
+    .await
+    .unwrap();
+
+    let params = VerificationParameters::new(AnyDidMethod::default().into_vm_resolver());
+    let result = verify(&vc, &DATA, params).await.unwrap();
+    assert_eq!(result, Ok(()));
+
+    let params = VerificationParameters::new(AnyDidMethod::default().into_vm_resolver());
+    let result =
+        optical_barcode_credential::verify_from_optical_data_hash(&vc, hash.as_slice(), params)
+            .await
+            .unwrap();
+    assert_eq!(result, Ok(()));
+
+    let params = VerificationParameters::new(AnyDidMethod::default().into_vm_resolver());
+    let result =
+        optical_barcode_credential::verify_from_optical_data_hash(&vc, optical_data, params)
+            .await
+            .unwrap();
+    assert!(result.is_err());
+
+    let input = load_unsigned::<MachineReadableZone>("tests/mrz/unsecured.jsonld");
+    let jwk = JWK::generate_p256();
+    let vm = DIDKey::generate_url(&jwk).unwrap();
+    let params = SignatureParameters::new(
+        AnyDidMethod::default().into_vm_resolver(),
+        SingleSecretSigner::new(jwk),
+        None,
+    );
+    assert!(optical_barcode_credential::sign_from_optical_data_hash(
+        input,
+        [0u8; 48],
+        ecdsa_xi_2023::proof_options(vm.into_iri()),
+        params,
+    )
+    .await
+    .is_err())
+}
+
+#[async_std::test]
+async fn mrz_verify_min_curve() {
+    let vc = load_signed::<MachineReadableZone>("tests/mrz/secured.jsonld");
+
+    let params = VerificationParameters::new(AnyDidMethod::default().into_vm_resolver())
+        .require_min_curve(ecdsa_xi_2023::Curve::P256);
+    let result = verify(&vc, &DATA, params).await.unwrap();
+    assert_eq!(result, Ok(()));
+
+    let params = VerificationParameters::new(AnyDidMethod::default().into_vm_resolver())
+        .require_min_curve(ecdsa_xi_2023::Curve::P384);
+    let error = verify(&vc, &DATA, params).await.unwrap_err();
+    assert!(error.to_string().contains("key curve below policy"));
+
+    let params = VerificationParameters::new(AnyDidMethod::default().into_vm_resolver())
+        .require_min_curve(ecdsa_xi_2023::Curve::P384);
+    let outcome = optical_barcode_credential::verify_detailed(&vc, &DATA, params)
+        .await
+        .unwrap();
+    assert_eq!(outcome, VerificationOutcome::WeakKey)
+}
+
+/// `did:key` resolver counting resolutions.
+struct CountingResolver<'a>(&'a AtomicUsize);
+
+impl VerificationMethodResolver for CountingResolver<'_> {
+    type Method = Multikey;
+
+    async fn resolve_verification_method_with(
+        &self,
+        issuer: Option<&Iri>,
+        method: Option<ReferenceOrOwnedRef<'_, Multikey>>,
+        options: ResolutionOptions,
+    ) -> Result<Cow<Multikey>, VerificationMethodResolutionError> {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        DidKeyResolver
+            .resolve_verification_method_with(issuer, method, options)
+            .await
+    }
+}
+
+#[async_std::test]
+async fn mrz_verify_caching_resolver() {
+    let vc = load_signed::<MachineReadableZone>("tests/mrz/secured.jsonld");
+
+    for (ttl, resolutions) in [(Duration::from_secs(60), 1), (Duration::ZERO, 2)] {
+        let count = AtomicUsize::new(0);
+        let resolver = CachingVmResolver::new(CountingResolver(&count), ttl);
+
+        for _ in 0..2 {
+            let params = VerificationParameters::new(&resolver);
+            let result = verify(&vc, &DATA, params).await.unwrap();
+            assert_eq!(result, Ok(()));
+        }
+
+        assert_eq!(count.load(Ordering::Relaxed), resolutions);
+
+        resolver.clear();
+        let params = VerificationParameters::new(&resolver);
+        let result = verify(&vc, &DATA, params).await.unwrap();
+        assert_eq!(result, Ok(()));
+        assert_eq!(count.load(Ordering::Relaxed), resolutions + 1)
+    }
+}
+
+#[async_std::test]
+async fn mrz_verify_with_key() {
+    let vc = load_signed::<MachineReadableZone>("tests/mrz/secured.jsonld");
+    let optical_data = vc.credential_subjects[0].create_optical_data(&DATA);
+
+    let key = DidKeyResolver::resolve(vc.verification_method_id().unwrap()).unwrap();
+    let params = VerificationParameters::new(()).ignore_status(true);
+    let result = optical_barcode_credential::verify_with_key(&vc, &optical_data, &key, params)
+        .await
+        .unwrap();
+    assert_eq!(result, Ok(()));
+
+    let other = DIDKey::generate_url(&JWK::generate_p256()).unwrap();
+    let other = DidKeyResolver::resolve(&other.into_iri()).unwrap();
+    let params = VerificationParameters::new(()).ignore_status(true);
+    let result =
+        optical_barcode_credential::verify_with_key(&vc, &optical_data, &other, params).await;
+    assert!(!matches!(result, Ok(Ok(()))))
+}
+
+#[async_std::test]
+async fn mrz_portrait_hash() {
+    let jwk = JWK::generate_p256();
+    let vm = DIDKey::generate_url(&jwk).unwrap();
+    let options = ecdsa_xi_2023::proof_options(vm.into_iri());
+
+    let params = SignatureParameters::new(
+        AnyDidMethod::default().into_vm_resolver(),
+        SingleSecretSigner::new(jwk),
+        None,
+    );
+
+    let vc = optical_barcode_credential::create_with_portrait(
+        &DATA,
+        b"portrait",
+        Uri::new("http://example.org/issuer").unwrap().to_owned(),
+        MachineReadableZone {},
+        options,
+        params,
+    )
+    .await
+    .unwrap();
+
+    // Nothing in the credential tells that a portrait is bound to it.
+    let json = json_syntax::to_value(&vc.credential_subjects[0]).unwrap();
+    assert_eq!(
+        json.compact_print().to_string(),
+        r#"{"type":"MachineReadableZone"}"#
+    );
+
+    let params = VerificationParameters::new(AnyDidMethod::default().into_vm_resolver());
+    let result = optical_barcode_credential::verify_with_portrait(&vc, &DATA, b"portrait", params)
+        .await
+        .unwrap();
+    assert_eq!(result, Ok(()));
+
+    let params = VerificationParameters::new(AnyDidMethod::default().into_vm_resolver());
+    let result = optical_barcode_credential::verify_with_portrait(&vc, &DATA, b"swapped", params)
+        .await
+        .unwrap();
+    assert!(result.is_err());
+
+    let xi = PortraitBinding::new(DATA, b"portrait");
+    let params = VerificationParameters::new(AnyDidMethod::default().into_vm_resolver());
+    let result = verify(&vc, &xi, params).await.unwrap();
+    assert_eq!(result, Ok(()))
+}
+
+#[async_std::test]
+async fn mrz_proof_expiry() {
+    let input = load_unsigned::<MachineReadableZone>("tests/mrz/unsecured.jsonld");
+    let options = load_proof_configuration("tests/mrz/configuration.jsonld").into_options();
+
+    let expires: DateTime<Utc> = "2000-01-01T00:00:00Z".parse().unwrap();
+    let params = SignatureParameters::new(
+        AnyDidMethod::default().into_vm_resolver(),
+        SingleSecretSigner::new(JWK::generate_p256()),
+        None,
+    )
+    .with_proof_expiry(expires);
+
+    let vc = optical_barcode_credential::sign(input, &DATA, options, params)
+        .await
+        .unwrap();
+    assert!(vc.proofs.iter().all(|proof| proof.expires.is_some()));
+
+    let params = VerificationParameters::new(AnyDidMethod::default().into_vm_resolver());
+    let outcome = optical_barcode_credential::verify_detailed(&vc, &DATA, params)
+        .await
+        .unwrap();
+    assert_eq!(outcome, VerificationOutcome::ProofExpired);
+
+    let params = VerificationParameters::new(AnyDidMethod::default().into_vm_resolver());
+    let error = verify(&vc, &DATA, params).await.unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        ProofValidationError::other("proof expired").to_string()
+    )
+}
+
+#[async_std::test]
+async fn mrz_verify_expired() {
+    let mut input = load_unsigned::<MachineReadableZone>("tests/mrz/unsecured.jsonld");
+    input.valid_until = Some("2000-01-01T00:00:00Z".parse().unwrap());
+
+    let jwk = JWK::generate_p256();
+    let vm = DIDKey::generate_url(&jwk).unwrap();
+    let options = ecdsa_xi_2023::proof_options(vm.into_iri());
+    let params = SignatureParameters::new(
+        AnyDidMethod::default().into_vm_resolver(),
+        SingleSecretSigner::new(jwk),
+        None,
+    );
+
+    let vc = optical_barcode_credential::sign(input, &DATA, options, params)
+        .await
+        .unwrap();
+
+    let params = VerificationParameters::new(AnyDidMethod::default().into_vm_resolver());
+    let error = verify(&vc, &DATA, params).await.unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        ProofValidationError::other("expired").to_string()
+    );
+
+    let params = VerificationParameters::new(AnyDidMethod::default().into_vm_resolver());
+    let outcome = optical_barcode_credential::verify_detailed(&vc, &DATA, params)
+        .await
+        .unwrap();
+    assert_eq!(outcome, VerificationOutcome::Expired);
+
+    let params = VerificationParameters::new(AnyDidMethod::default().into_vm_resolver())
+        .require_valid_period(false);
+    let result = verify(&vc, &DATA, params).await.unwrap();
+    assert_eq!(result, Ok(()))
+}
+
+#[async_std::test]
+async fn mrz_verify_batch() {
+    let optical_data = MachineReadableZone {}.create_optical_data(&DATA).to_vec();
+    let items = vec![
+        (
+            load_signed::<MachineReadableZone>("tests/mrz/secured.jsonld"),
+            optical_data.clone(),
+        ),
+        (
+            load_signed::<MachineReadableZone>("tests/mrz/secured.jsonld"),
+            vec![0; 32],
+        ),
+        (
+            load_signed::<MachineReadableZone>("tests/mrz/secured.jsonld"),
+            optical_data,
+        ),
+    ];
+
+    let count = AtomicUsize::new(0);
+    let params = VerificationParameters::new(CountingResolver(&count));
+
+    let results = optical_barcode_credential::verify_batch(&items, params).await;
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].as_ref().unwrap(), &Ok(()));
+    assert!(!matches!(results[1], Ok(Ok(()))));
+    assert_eq!(results[2].as_ref().unwrap(), &Ok(()));
+
+    // The three items share the same verification method.
+    assert_eq!(count.load(Ordering::Relaxed), 1)
+}
+
 const COMPRESSED: &str = "d90664a50183198000198001198002189d82187618a418baa1189c18a218be18ae18c0a5189c186c18d20418dc18e218de58417a9ec7f688f60caa8c757592250b3f6d6e18419941f186e1ed4245770e687502d51d01cd2c2295e4338178a51a35c2f044a85598e15db9aef00261bc5c95a744e718e018b0";
 
 #[async_std::test]
@@ -63,22 +581,56 @@ async fn mrz_compress() {
 #[async_std::test]
 async fn mrz_decompress() {
     let input = hex::decode(COMPRESSED).unwrap();
-    let output = json_syntax::to_value(
-        optical_barcode_credential::decode_from_bytes::<MachineReadableZone>(&input)
-            .await
-            .unwrap(),
-    )
-    .unwrap();
-    let expected = json_syntax::to_value(load_signed::<MachineReadableZone>(
-        "tests/mrz/secured.jsonld",
-    ))
-    .unwrap();
-    if output != expected {
-        eprintln!("output: {}", output.pretty_print());
+    let output = optical_barcode_credential::decode_from_bytes::<MachineReadableZone>(&input)
+        .await
+        .unwrap();
+    let expected = load_signed::<MachineReadableZone>("tests/mrz/secured.jsonld");
+    if !output.semantically_eq(&expected) {
+        eprintln!(
+            "output: {}",
+            json_syntax::to_value(output).unwrap().pretty_print()
+        );
         panic!("invalid decompression")
     }
 }
 
+#[async_std::test]
+async fn mrz_decompress_to_json() {
+    let input = hex::decode(COMPRESSED).unwrap();
+    let json = optical_barcode_credential::decode_to_json(&input)
+        .await
+        .unwrap();
+    let expected = load_signed::<MachineReadableZone>("tests/mrz/secured.jsonld");
+    let typed: optical_barcode_credential::VerifiableOpticalBarcodeCredential<MachineReadableZone> =
+        json_syntax::from_value(json).unwrap();
+    assert!(typed.semantically_eq(&expected))
+}
+
+#[async_std::test]
+async fn mrz_decompress_limits() {
+    use optical_barcode_credential::{DecodeError, DecodeLimits};
+
+    let input = hex::decode(COMPRESSED).unwrap();
+
+    for limits in [
+        DecodeLimits {
+            max_json_bytes: 100,
+            ..DecodeLimits::default()
+        },
+        DecodeLimits {
+            max_depth: 1,
+            ..DecodeLimits::default()
+        },
+    ] {
+        let result =
+            optical_barcode_credential::decode_from_bytes_with_limits::<MachineReadableZone>(
+                &input, limits,
+            )
+            .await;
+        assert!(matches!(result, Err(DecodeError::TooLarge)))
+    }
+}
+
 const QR_CODE_PAYLOAD: &str = "VC1-RSJRPWCR803A3P0098G3A3-B02-J743853U53KGK0XJ6MKJ1OI0M.FO053.33963DN04$RAQS+4SMC8C3KM7VX4VAPL9%EILI:I1O$D:23%GJ0OUCPS0H8D2FB9D5G00U39.PXG49%SOGGB*K$Z6%GUSCLWEJ8%B95MOD0P NG-I:V8N63K53";
 
 #[test]
@@ -88,9 +640,236 @@ fn mrz_qr_code_encode() {
     assert_eq!(qr_data, QR_CODE_PAYLOAD);
 }
 
+#[test]
+fn mrz_qr_code_estimate_len() {
+    let input = hex::decode(COMPRESSED).unwrap();
+    for len in [0, 1, 2, 3, input.len() - 1, input.len()] {
+        assert_eq!(
+            MachineReadableZone::estimate_qr_payload_len(&input[..len]),
+            MachineReadableZone::encode_qr_code_payload(&input[..len]).len()
+        );
+    }
+}
+
+proptest! {
+    #[test]
+    fn mrz_base45_round_trip(bytes in prop::collection::vec(any::<u8>(), 0..1024)) {
+        let encoded = multibase45_encode(&bytes);
+        prop_assert_eq!(multibase45_decode(&encoded).unwrap(), bytes);
+    }
+}
+
+#[test]
+fn mrz_base45_trailing_overflow() {
+    // `::` decodes to 44 + 44 * 45, which does not fit in a byte.
+    assert!(multibase45_decode("R::").is_err());
+    assert_eq!(multibase45_decode("RU5").unwrap(), [255])
+}
+
 #[test]
 fn mrz_qr_code_decode() {
     let bytes = MachineReadableZone::decode_qr_code_payload(QR_CODE_PAYLOAD).unwrap();
     let hex = hex::encode(&bytes);
     assert_eq!(hex, COMPRESSED);
 }
+
+#[test]
+fn mrz_qr_code_decode_lenient() {
+    let (head, tail) = QR_CODE_PAYLOAD.split_at(40);
+    let scanned = format!("  {head}\r\n{tail}\n");
+    assert!(MachineReadableZone::decode_qr_code_payload(&scanned).is_err());
+
+    let bytes = MachineReadableZone::decode_qr_code_payload_lenient(&scanned).unwrap();
+    assert_eq!(hex::encode(&bytes), COMPRESSED);
+}
+
+#[test]
+fn mrz_barcode_source() {
+    let input = hex::decode(COMPRESSED).unwrap();
+    assert_eq!(
+        QrCodePayload(QR_CODE_PAYLOAD).read_payload().unwrap(),
+        input
+    );
+    assert_eq!(input.read_payload().unwrap(), input);
+    assert!(QrCodePayload("VC1-Rab").read_payload().is_err())
+}
+
+#[test]
+fn mrz_qr_code_compat() {
+    let input = hex::decode(COMPRESSED).unwrap();
+    assert_eq!(
+        MachineReadableZone::decode_qr_code_payload_compat(QR_CODE_PAYLOAD).unwrap(),
+        input
+    );
+
+    let legacy = QR_CODE_PAYLOAD.replacen("VC1-R", "VC1-", 1);
+    assert!(MachineReadableZone::decode_qr_code_payload(&legacy).is_err());
+    assert_eq!(
+        MachineReadableZone::decode_qr_code_payload_compat(&legacy).unwrap(),
+        input
+    );
+}
+
+#[test]
+fn mrz_aztec_code() {
+    let input = hex::decode(COMPRESSED).unwrap();
+    let aztec_data = MachineReadableZone::encode_aztec_payload(&input);
+    assert_eq!(
+        MachineReadableZone::decode_qr_code_payload(&aztec_data).unwrap(),
+        input
+    );
+    assert_eq!(
+        MachineReadableZone::decode_aztec_payload(QR_CODE_PAYLOAD).unwrap(),
+        input
+    );
+}
+
+#[async_std::test]
+async fn mrz_uncompressed_round_trip() {
+    let vc = load_signed::<MachineReadableZone>("tests/mrz/secured.jsonld");
+    let config = optical_barcode_credential::EncodeConfig::new(
+        optical_barcode_credential::CompressionMode::Uncompressed,
+    );
+    let bytes = optical_barcode_credential::encode_to_bytes_with(&vc, config).await;
+    let decoded = optical_barcode_credential::decode_from_bytes::<MachineReadableZone>(&bytes)
+        .await
+        .unwrap();
+    assert_eq!(
+        json_syntax::to_value(decoded).unwrap(),
+        json_syntax::to_value(vc).unwrap()
+    )
+}
+
+#[async_std::test]
+async fn mrz_decode_any() {
+    let input = hex::decode(COMPRESSED).unwrap();
+    let vc = optical_barcode_credential::decode_any_from_bytes(&input)
+        .await
+        .unwrap();
+    assert!(matches!(
+        vc,
+        optical_barcode_credential::DecodedVcb::MachineReadableZone(_)
+    ))
+}
+
+#[test]
+fn mrz_subject_serde() {
+    let json = json_syntax::to_value(MachineReadableZone {}).unwrap();
+    assert_eq!(
+        json.compact_print().to_string(),
+        r#"{"type":"MachineReadableZone"}"#
+    );
+    json_syntax::from_value::<MachineReadableZone>(json).unwrap();
+
+    for invalid in [
+        r#"{"type":"AamvaDriversLicenseScannableInformation"}"#,
+        r#"{"type":["MachineReadableZone"]}"#,
+        r#"{}"#,
+    ] {
+        let json = json_syntax::Value::parse_str(invalid).unwrap().0;
+        assert!(json_syntax::from_value::<MachineReadableZone>(json).is_err())
+    }
+}
+
+#[test]
+fn mrz_validate_structure() {
+    let input = load_unsigned::<MachineReadableZone>("tests/mrz/unsecured.jsonld");
+    input.validate_structure().unwrap();
+
+    let mut json = json_syntax::to_value(&input).unwrap();
+    json.as_object_mut().unwrap().insert(
+        "type".into(),
+        json_syntax::Value::String("VerifiableCredential".into()),
+    );
+    assert!(optical_barcode_credential::validate_json_structure(&json).is_err());
+}
+
+#[test]
+fn mrz_reversed_contexts() {
+    let content = std::fs::read_to_string("tests/mrz/secured.jsonld").unwrap();
+    let mut json = json_syntax::Value::parse_str(&content).unwrap().0;
+
+    // Trailing contexts are accepted.
+    optical_barcode_credential::validate_json_structure(&json).unwrap();
+
+    let context = json
+        .as_object_mut()
+        .unwrap()
+        .get_unique_mut("@context")
+        .unwrap()
+        .unwrap()
+        .as_array_mut()
+        .unwrap();
+    context.reverse();
+    assert_eq!(context[0].as_str(), Some(UtopiaV2::CONTEXT_IRI.as_str()));
+
+    let error = optical_barcode_credential::validate_json_structure(&json).unwrap_err();
+    assert!(matches!(
+        error,
+        optical_barcode_credential::StructureError::MissingContext(iri)
+            if iri == V2::CONTEXT_IRI
+    ))
+}
+
+#[test]
+fn mrz_builtin_context_iris() {
+    let iris = optical_barcode_credential::builtin_context_iris();
+    assert_eq!(iris.len(), CONTEXT_LOADER.len());
+    assert!(iris.contains(&UtopiaV2::CONTEXT_IRI));
+    assert!(iris.windows(2).all(|w| w[0] < w[1]))
+}
+
+#[async_std::test]
+async fn mrz_utopia_context() {
+    assert!(CONTEXT_LOADER.contains_key(UtopiaV2::CONTEXT_IRI));
+
+    let vc = load_signed::<MachineReadableZone>("tests/mrz/secured.jsonld");
+    let json = json_syntax::to_value(&vc).unwrap();
+    assert!(json.as_object().unwrap().get("@context").any(|c| c
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|c| c.as_str() == Some(UtopiaV2::CONTEXT_IRI.as_str()))));
+
+    // The issuer and status list terms are only defined by the Utopia context.
+    let bytes = optical_barcode_credential::encode_to_bytes(&vc).await;
+    let decoded = optical_barcode_credential::decode_from_bytes::<MachineReadableZone>(&bytes)
+        .await
+        .unwrap();
+    assert!(decoded.semantically_eq(&vc));
+}
+
+#[cfg(feature = "testing")]
+#[async_std::test]
+async fn mrz_deterministic_round_trip() {
+    use w3c_vc_barcodes::testing;
+
+    // `tests/conformance/mrz/proof-value.txt`, computed independently of this
+    // crate from the RFC 6979 signature of the `ecdsa-xi-2023` hash data. The
+    // rest of the CBOR-LD encoding is checked against the specification by the
+    // conformance test.
+    const PROOF_VALUE: &str =
+        "z5EBVuGTMafd6hsgWgS8zLWtGybG4UEQDibY55q7Gz6VHLmx3iyNE3RpujtVC1YAZE5HrH6EnoETtWeohEgxPS728";
+
+    let mut payloads = Vec::new();
+    for _ in 0..2 {
+        let input = load_unsigned::<MachineReadableZone>("tests/mrz/unsecured.jsonld");
+        let vc = testing::sign(input, &DATA).await.unwrap();
+        assert_eq!(proof_value(&vc), PROOF_VALUE);
+
+        let bytes = optical_barcode_credential::encode_to_bytes(&vc).await;
+        payloads.push(MachineReadableZone::encode_qr_code_payload(&bytes));
+    }
+
+    assert_eq!(payloads[0], payloads[1]);
+
+    let bytes = MachineReadableZone::decode_qr_code_payload(&payloads[0]).unwrap();
+    let vc = optical_barcode_credential::decode_from_bytes::<MachineReadableZone>(&bytes)
+        .await
+        .unwrap();
+    assert_eq!(proof_value(&vc), PROOF_VALUE);
+
+    let params = VerificationParameters::new(AnyDidMethod::default().into_vm_resolver());
+    let result = verify(&vc, &DATA, params).await.unwrap();
+    assert_eq!(result, Ok(()))
+}